@@ -1,5 +1,14 @@
 pub const TILE_SIZE: f32 = 50.0;
 pub const ROWS: u32 = 20;
 pub const COLS: u32 = 10;
+/// Rows at the top of the `Matrix` that are not drawn, above the visible `ROWS`.
+pub const VANISH_ROWS: u32 = 20;
 
-pub const FALL_SPEED_UP: f64 = 0.001;
+/// Width of the panel to the right of the well that shows the next queue.
+pub const SIDE_PANEL_WIDTH: f32 = 4.0 * TILE_SIZE;
+/// How much smaller than the active piece the queued pieces are drawn.
+pub const PREVIEW_SCALE: f32 = 0.6;
+pub const MAX_PREVIEW: usize = 6;
+
+/// Highest level that can be picked to start from on the title screen.
+pub const MAX_START_LEVEL: u32 = 15;
@@ -0,0 +1,1075 @@
+//! The rules of the game, with no dependency on Bevy.
+//!
+//! A `Game` is advanced with `step`, which takes the state of every input
+//! and the time since the previous step, and reports what happened as
+//! `Event`s. Rendering and reading devices is left to the caller.
+
+use std::{collections::VecDeque, time::Duration};
+
+use crate::{
+    consts::{COLS, ROWS, VANISH_ROWS},
+    handling::{AutoShift, Handling, ShiftDirection},
+    lock_delay::{LockDelay, LockReset},
+    matrix::{Matrix, check_for_colision, fix_piece, is_lock_out},
+    pieces::{Piece, PieceIndicies, PieceKind, Table},
+    randomizer::{BoxedRandomizer, RandomizerKind},
+    scoring::{ClearTracker, LockScore, gravity_interval, level_for},
+    tspin::{TSpin, detect_tspin},
+};
+
+/// Everything the player can do, independent of the device used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    HardDrop,
+    RotateCw,
+    RotateCcw,
+    Rotate180,
+    Hold,
+    Pause,
+    Restart,
+}
+
+impl Action {
+    pub const ALL: [Action; 10] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::SoftDrop,
+        Action::HardDrop,
+        Action::RotateCw,
+        Action::RotateCcw,
+        Action::Rotate180,
+        Action::Hold,
+        Action::Pause,
+        Action::Restart,
+    ];
+
+    /// Name of the action in the bindings file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "left",
+            Action::MoveRight => "right",
+            Action::SoftDrop => "soft_drop",
+            Action::HardDrop => "hard_drop",
+            Action::RotateCw => "rotate_cw",
+            Action::RotateCcw => "rotate_ccw",
+            Action::Rotate180 => "rotate_180",
+            Action::Hold => "hold",
+            Action::Pause => "pause",
+            Action::Restart => "restart",
+        }
+    }
+
+    fn bit(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+/// Which actions are held during a step, and which of them went down in it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Inputs {
+    held: u16,
+    pressed: u16,
+}
+
+impl Inputs {
+    /// Marks `action` as held, and as just pressed if `just_pressed` is set.
+    pub fn press(&mut self, action: Action, just_pressed: bool) {
+        self.held |= action.bit();
+
+        if just_pressed {
+            self.pressed |= action.bit();
+        }
+    }
+
+    pub fn held(&self, action: Action) -> bool {
+        self.held & action.bit() != 0
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.pressed & action.bit() != 0
+    }
+
+    /// How many actions went down in this step, not counting pause and restart.
+    pub fn presses(&self) -> u32 {
+        let ignored = Action::Pause.bit() | Action::Restart.bit();
+        (self.pressed & !ignored).count_ones()
+    }
+}
+
+/// Something that happened during a step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A new active piece appeared at the top of the matrix.
+    Spawned(PieceKind),
+    /// The active piece went into hold.
+    Held(PieceKind),
+    Locked {
+        kind: PieceKind,
+        /// Cells the piece filled, as `(row, col)`, before any rows were cleared.
+        cells: Vec<(usize, usize)>,
+        /// Rows removed by this lock, top to bottom.
+        cleared: Vec<usize>,
+        tspin: TSpin,
+        score: LockScore,
+    },
+    LevelUp(u32),
+    /// The stack topped out.
+    GameOver,
+    /// The mode's goal was reached.
+    Finished,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Stats {
+    pub score: u32,
+    pub level: u32,
+    pub lines: u32,
+    pub pieces: u32,
+    /// Actions pressed, for inputs per piece.
+    pub inputs: u32,
+    /// Garbage lines sent, counted even with no opponent to receive them.
+    pub attack: u32,
+    /// Time played, not counting pauses since the game is only stepped while running.
+    pub time: Duration,
+}
+
+/// What a game is played for. Every mode also ends when the stack tops out.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// Clear 150 lines, speeding up every 10.
+    #[default]
+    Marathon,
+    /// Clear 40 lines as fast as possible.
+    Sprint,
+    /// Score as much as possible in two minutes.
+    Ultra,
+    /// No goal, play until topping out.
+    Endless,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 4] = [Mode::Marathon, Mode::Sprint, Mode::Ultra, Mode::Endless];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Marathon => "marathon",
+            Mode::Sprint => "sprint",
+            Mode::Ultra => "ultra",
+            Mode::Endless => "endless",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Mode::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+
+    /// Whether attack is worth showing, in the modes played for score
+    /// rather than towards a line goal.
+    pub fn counts_attack(self) -> bool {
+        matches!(self, Mode::Ultra | Mode::Endless)
+    }
+
+    pub fn line_goal(self) -> Option<u32> {
+        match self {
+            Mode::Marathon => Some(150),
+            Mode::Sprint => Some(40),
+            Mode::Ultra | Mode::Endless => None,
+        }
+    }
+
+    fn time_limit(self) -> Option<Duration> {
+        match self {
+            Mode::Ultra => Some(Duration::from_secs(120)),
+            _ => None,
+        }
+    }
+}
+
+/// How a game ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ending {
+    ToppedOut,
+    GoalReached,
+}
+
+/// Options chosen before a game starts.
+#[derive(Debug, Clone)]
+pub struct GameConfig {
+    /// How many times faster gravity runs while soft drop is held.
+    pub soft_drop_factor: f64,
+    pub handling: Handling,
+    pub lock_delay: Duration,
+    pub lock_reset: LockReset,
+    pub randomizer: RandomizerKind,
+    /// Fixed seed for reproducible games. A fresh one is picked per game if `None`.
+    pub seed: Option<u64>,
+    /// How many upcoming pieces to show, up to `MAX_PREVIEW`.
+    pub preview: usize,
+    pub mode: Mode,
+    pub start_level: u32,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            soft_drop_factor: 20.0,
+            handling: Handling::default(),
+            lock_delay: Duration::from_millis(500),
+            lock_reset: LockReset::default(),
+            randomizer: RandomizerKind::default(),
+            seed: None,
+            preview: 5,
+            mode: Mode::default(),
+            start_level: 1,
+        }
+    }
+}
+
+/// The piece under the player's control.
+pub struct ActivePiece {
+    piece: Piece,
+    position: PieceIndicies,
+    /// Kick used by the last successful maneuver if it was a rotation,
+    /// `None` if the piece has moved since.
+    last_kick: Option<usize>,
+}
+
+impl ActivePiece {
+    pub fn kind(&self) -> PieceKind {
+        self.piece.get_kind()
+    }
+
+    pub fn rotation(&self) -> usize {
+        self.piece.get_rotation()
+    }
+
+    pub fn table(&self) -> &'static Table {
+        self.piece.get_table()
+    }
+
+    pub fn position(&self) -> PieceIndicies {
+        self.position
+    }
+}
+
+/// A single game, from the first piece to the top-out.
+pub struct Game {
+    config: GameConfig,
+    matrix: Matrix,
+    active: Option<ActivePiece>,
+    randomizer: BoxedRandomizer,
+    /// Upcoming pieces, front first. Always holds exactly the preview count.
+    queue: VecDeque<PieceKind>,
+    hold: Option<PieceKind>,
+    /// Only one swap is allowed until the active piece locks.
+    hold_used: bool,
+    /// Time accumulated towards the next gravity drop.
+    fall: Duration,
+    lock_delay: LockDelay,
+    auto_shift: AutoShift,
+    tracker: ClearTracker,
+    stats: Stats,
+    ending: Option<Ending>,
+    events: Vec<Event>,
+}
+
+impl Game {
+    pub fn new(config: GameConfig, seed: u64) -> Self {
+        let mut randomizer = config.randomizer.create(seed);
+        let queue = (0..config.preview).map(|_| randomizer.next()).collect();
+
+        let mut game = Game {
+            matrix: Matrix::try_new(COLS as usize, (ROWS + VANISH_ROWS) as usize).unwrap(),
+            active: None,
+            randomizer,
+            queue,
+            hold: None,
+            hold_used: false,
+            fall: Duration::ZERO,
+            lock_delay: LockDelay::new(config.lock_reset, config.lock_delay),
+            auto_shift: AutoShift::default(),
+            tracker: ClearTracker::default(),
+            stats: Stats {
+                level: config.start_level,
+                ..Default::default()
+            },
+            ending: None,
+            events: Vec::new(),
+            config,
+        };
+
+        let kind = game.next_kind();
+        game.spawn(kind);
+        game
+    }
+
+    pub fn matrix(&self) -> &Matrix {
+        &self.matrix
+    }
+
+    pub fn active(&self) -> Option<&ActivePiece> {
+        self.active.as_ref()
+    }
+
+    /// Where the active piece would land if it were hard dropped.
+    pub fn ghost(&self) -> Option<PieceIndicies> {
+        self.active
+            .as_ref()
+            .map(|active| landing_position(&self.matrix, active.table(), &active.position))
+    }
+
+    pub fn queue(&self) -> impl Iterator<Item = PieceKind> + '_ {
+        self.queue.iter().copied()
+    }
+
+    pub fn hold(&self) -> Option<PieceKind> {
+        self.hold
+    }
+
+    pub fn hold_used(&self) -> bool {
+        self.hold_used
+    }
+
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.config.mode
+    }
+
+    /// How the game ended, `None` while it's still going.
+    pub fn ending(&self) -> Option<Ending> {
+        self.ending
+    }
+
+    /// Advances the game by `delta` with `inputs` applied at the start,
+    /// returning what happened in order.
+    pub fn step(&mut self, inputs: &Inputs, delta: Duration) -> Vec<Event> {
+        if self.ending.is_none() {
+            self.stats.inputs += inputs.presses();
+            self.apply_inputs(inputs, delta);
+        }
+
+        if self.ending.is_none() && !inputs.just_pressed(Action::HardDrop) {
+            self.apply_gravity(inputs.held(Action::SoftDrop), delta);
+        }
+
+        if self.ending.is_none() {
+            self.update_lock_delay(delta);
+        }
+
+        if self.ending.is_none() {
+            self.stats.time += delta;
+
+            if let Some(limit) = self.config.mode.time_limit()
+                && self.stats.time >= limit
+            {
+                self.end(Ending::GoalReached);
+            }
+        }
+
+        std::mem::take(&mut self.events)
+    }
+
+    fn apply_inputs(&mut self, inputs: &Inputs, delta: Duration) {
+        if inputs.just_pressed(Action::Hold) && !self.hold_used {
+            self.swap_hold();
+
+            // The swapped in piece was blocked out.
+            if self.ending.is_some() {
+                return;
+            }
+        }
+
+        let Some(active) = self.active.as_mut() else {
+            return;
+        };
+
+        if !(inputs.held(Action::RotateCcw) && inputs.held(Action::RotateCw)) {
+            let mut kick = None;
+
+            if inputs.just_pressed(Action::RotateCcw) {
+                kick = try_rotate_piece(&self.matrix, active, Rotation::Ccw);
+            }
+
+            if inputs.just_pressed(Action::RotateCw) {
+                kick = try_rotate_piece(&self.matrix, active, Rotation::Cw);
+            }
+
+            if inputs.just_pressed(Action::Rotate180) {
+                kick = try_rotate_piece(&self.matrix, active, Rotation::Half);
+            }
+
+            if kick.is_some() {
+                active.last_kick = kick;
+                self.lock_delay.on_move();
+            }
+        }
+
+        let shift = self.auto_shift.update(
+            inputs.just_pressed(Action::MoveLeft),
+            inputs.just_pressed(Action::MoveRight),
+            inputs.held(Action::MoveLeft),
+            inputs.held(Action::MoveRight),
+            delta,
+            &self.config.handling,
+        );
+
+        if let Some((shift_direction, cells)) = shift {
+            let direction = match shift_direction {
+                ShiftDirection::Left => Direction::Left,
+                ShiftDirection::Right => Direction::Right,
+            };
+            let mut moved = false;
+
+            for _ in 0..cells {
+                if !try_move_piece(
+                    &self.matrix,
+                    active.table(),
+                    &mut active.position,
+                    direction,
+                ) {
+                    break;
+                }
+                moved = true;
+            }
+
+            if moved {
+                active.last_kick = None;
+                self.lock_delay.on_move();
+            }
+        }
+
+        if inputs.just_pressed(Action::HardDrop) {
+            let landing = landing_position(&self.matrix, active.table(), &active.position);
+            let cells = (landing.i - active.position.i) as u32;
+
+            if cells > 0 {
+                active.last_kick = None;
+            }
+
+            active.position = landing;
+            self.stats.score += 2 * cells;
+            self.lock();
+        }
+    }
+
+    fn apply_gravity(&mut self, soft_drop: bool, delta: Duration) {
+        let Some(active) = self.active.as_mut() else {
+            return;
+        };
+
+        let interval = gravity_interval(self.stats.level);
+        self.fall += if soft_drop {
+            delta.mul_f64(self.config.soft_drop_factor)
+        } else {
+            delta
+        };
+
+        while self.fall >= interval {
+            self.fall -= interval;
+
+            if !try_move_piece(
+                &self.matrix,
+                active.table(),
+                &mut active.position,
+                Direction::Down,
+            ) {
+                self.fall = Duration::ZERO;
+                break;
+            }
+
+            active.last_kick = None;
+
+            if soft_drop {
+                self.stats.score += 1;
+            }
+        }
+    }
+
+    fn update_lock_delay(&mut self, delta: Duration) {
+        let Some(active) = self.active.as_ref() else {
+            return;
+        };
+
+        let mut below = active.position;
+        move_piece(&mut below, Direction::Down);
+        let grounded = check_for_colision(&self.matrix, active.table(), &below);
+
+        if self.lock_delay.update(active.position.i, grounded, delta) {
+            self.lock();
+        }
+    }
+
+    fn swap_hold(&mut self) {
+        let Some(active) = self.active.take() else {
+            return;
+        };
+
+        let kind = active.kind();
+        let next = match self.hold.replace(kind) {
+            Some(held) => held,
+            None => self.next_kind(),
+        };
+
+        self.hold_used = true;
+        self.events.push(Event::Held(kind));
+        self.spawn(next);
+    }
+
+    fn next_kind(&mut self) -> PieceKind {
+        self.queue.push_back(self.randomizer.next());
+        self.queue.pop_front().unwrap()
+    }
+
+    fn spawn(&mut self, kind: PieceKind) {
+        self.auto_shift.on_spawn(&self.config.handling);
+        self.lock_delay = LockDelay::new(self.config.lock_reset, self.config.lock_delay);
+        self.fall = Duration::ZERO;
+
+        let piece = Piece::new(kind);
+        let mut position = spawn_position(piece.get_table());
+
+        // Block-out: the new piece overlaps the stack as soon as it appears.
+        let blocked = check_for_colision(&self.matrix, piece.get_table(), &position);
+
+        if !blocked {
+            try_move_piece(
+                &self.matrix,
+                piece.get_table(),
+                &mut position,
+                Direction::Down,
+            );
+        }
+
+        self.active = Some(ActivePiece {
+            piece,
+            position,
+            last_kick: None,
+        });
+        self.events.push(Event::Spawned(kind));
+
+        if blocked {
+            self.end(Ending::ToppedOut);
+        }
+    }
+
+    fn lock(&mut self) {
+        let Some(active) = self.active.take() else {
+            return;
+        };
+
+        let table = active.table();
+        let position = active.position;
+
+        let tspin = match (active.kind(), active.last_kick) {
+            (PieceKind::T, Some(kick)) => {
+                detect_tspin(&self.matrix, &position, active.rotation(), kick)
+            }
+            _ => TSpin::None,
+        };
+
+        let cells = table_cells(table, &position);
+        fix_piece(&mut self.matrix, table, &position, active.kind());
+
+        let cleared = self.matrix.clear_full_rows();
+
+        self.stats.pieces += 1;
+        self.stats.lines += cleared.len() as u32;
+        let perfect_clear = !cleared.is_empty() && self.matrix.is_empty();
+        let score = self
+            .tracker
+            .on_lock(cleared.len(), tspin, perfect_clear, self.stats.level);
+        self.stats.score += score.points;
+        self.stats.attack += score.attack;
+
+        self.events.push(Event::Locked {
+            kind: active.kind(),
+            cells,
+            cleared,
+            tspin,
+            score,
+        });
+
+        let level = level_for(self.config.start_level, self.stats.lines);
+        if level != self.stats.level {
+            self.stats.level = level;
+            self.events.push(Event::LevelUp(level));
+        }
+
+        self.hold_used = false;
+
+        if is_lock_out(table, &position) {
+            self.end(Ending::ToppedOut);
+            return;
+        }
+
+        if let Some(goal) = self.config.mode.line_goal()
+            && self.stats.lines >= goal
+        {
+            self.end(Ending::GoalReached);
+            return;
+        }
+
+        let kind = self.next_kind();
+        self.spawn(kind);
+    }
+
+    fn end(&mut self, ending: Ending) {
+        self.ending = Some(ending);
+        self.events.push(match ending {
+            Ending::ToppedOut => Event::GameOver,
+            Ending::GoalReached => Event::Finished,
+        });
+    }
+}
+
+/// Guideline spawn position: the piece's box starts in rows 21 and 22, the
+/// two lowest rows of the vanish zone, with the piece centred on columns 3 to 6.
+fn spawn_position(table: &Table) -> PieceIndicies {
+    let occupied: Vec<i32> = (0..4)
+        .filter(|&j| table.iter().any(|table_row| table_row[j] != 0))
+        .map(|j| j as i32)
+        .collect();
+    let left = occupied.first().copied().unwrap_or(0);
+    let width = occupied.last().copied().unwrap_or(0) - left + 1;
+
+    PieceIndicies {
+        i: VANISH_ROWS as i32 - 2,
+        j: (COLS as i32 - width) / 2 - left,
+    }
+}
+
+/// Matrix cells, as `(row, col)`, covered by `table` placed at `piece_indicies`.
+fn table_cells(table: &Table, piece_indicies: &PieceIndicies) -> Vec<(usize, usize)> {
+    let mut cells = Vec::new();
+
+    for (i, table_row) in table.iter().enumerate() {
+        for (j, &cell) in table_row.iter().enumerate() {
+            if cell != 0 {
+                cells.push((
+                    (piece_indicies.i + i as i32) as usize,
+                    (piece_indicies.j + j as i32) as usize,
+                ));
+            }
+        }
+    }
+
+    cells
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Left,
+    Right,
+    Down,
+}
+
+enum Rotation {
+    Cw,
+    Ccw,
+    Half,
+}
+
+fn move_piece(piece_indicies: &mut PieceIndicies, direction: Direction) {
+    match direction {
+        Direction::Left => {
+            piece_indicies.j -= 1;
+        }
+        Direction::Right => {
+            piece_indicies.j += 1;
+        }
+        Direction::Down => {
+            piece_indicies.i += 1;
+        }
+    }
+}
+
+/// Moves the piece only if the new position is free. Returns whether it moved.
+fn try_move_piece(
+    matrix: &Matrix,
+    table: &Table,
+    piece_indicies: &mut PieceIndicies,
+    direction: Direction,
+) -> bool {
+    let mut moved = *piece_indicies;
+    move_piece(&mut moved, direction);
+
+    if check_for_colision(matrix, table, &moved) {
+        return false;
+    }
+
+    *piece_indicies = moved;
+    true
+}
+
+/// Rotates the piece using the SRS kick offsets, taking the first one that fits.
+/// Returns the index of the kick used, or leaves everything untouched and
+/// returns `None` if none of them fit.
+fn try_rotate_piece(
+    matrix: &Matrix,
+    active: &mut ActivePiece,
+    rotation: Rotation,
+) -> Option<usize> {
+    let piece = &mut active.piece;
+    let from = piece.get_rotation();
+
+    match rotation {
+        Rotation::Cw => piece.rotate_cw(),
+        Rotation::Ccw => piece.rotate_ccw(),
+        Rotation::Half => {
+            piece.rotate_cw();
+            piece.rotate_cw();
+        }
+    }
+
+    let to = piece.get_rotation();
+
+    for (kick, &(x, y)) in piece.get_kicks(from, to).iter().enumerate() {
+        let kicked = PieceIndicies {
+            i: active.position.i - y,
+            j: active.position.j + x,
+        };
+
+        if !check_for_colision(matrix, piece.get_table(), &kicked) {
+            active.position = kicked;
+            return Some(kick);
+        }
+    }
+
+    match rotation {
+        Rotation::Cw => piece.rotate_ccw(),
+        Rotation::Ccw => piece.rotate_cw(),
+        Rotation::Half => {
+            piece.rotate_ccw();
+            piece.rotate_ccw();
+        }
+    }
+
+    None
+}
+
+/// Lowest position the piece can fall to from where it is now.
+fn landing_position(
+    matrix: &Matrix,
+    table: &Table,
+    piece_indicies: &PieceIndicies,
+) -> PieceIndicies {
+    let mut landing = *piece_indicies;
+    while try_move_piece(matrix, table, &mut landing, Direction::Down) {}
+    landing
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        consts::{COLS, ROWS, VANISH_ROWS},
+        engine::{
+            Action, ActivePiece, Direction, Ending, Event, Game, GameConfig, Inputs, Mode,
+            Rotation, spawn_position, table_cells, try_move_piece, try_rotate_piece,
+        },
+        matrix::{Cell, Matrix},
+        pieces::{Piece, PieceIndicies, PieceKind},
+    };
+
+    const FRAME: Duration = Duration::from_millis(16);
+
+    /// A new game with the first piece's `Spawned` event already consumed.
+    fn started(seed: u64) -> Game {
+        let mut game = Game::new(GameConfig::default(), seed);
+        let events = game.step(&Inputs::default(), Duration::ZERO);
+        assert!(matches!(events[..], [Event::Spawned(_)]));
+        game
+    }
+
+    /// An empty matrix the size of the one a game plays on.
+    fn empty_matrix() -> Matrix {
+        Matrix::try_new(COLS as usize, (ROWS + VANISH_ROWS) as usize).unwrap()
+    }
+
+    fn pressed(action: Action) -> Inputs {
+        let mut inputs = Inputs::default();
+        inputs.press(action, true);
+        inputs
+    }
+
+    #[test]
+    fn test_hard_drop_locks_and_spawns_next() {
+        let mut game = started(1);
+        let next = game.queue().next().unwrap();
+        let start = game.active().unwrap().position();
+        let landing = game.ghost().unwrap();
+
+        let events = game.step(&pressed(Action::HardDrop), FRAME);
+
+        let Event::Locked { kind, cells, .. } = &events[0] else {
+            panic!("expected a lock, got {events:?}");
+        };
+        assert_eq!(events[1], Event::Spawned(next));
+        assert_eq!(game.stats().pieces, 1);
+        assert_eq!(game.stats().inputs, 1);
+        assert_eq!(game.stats().score, 2 * (landing.i - start.i) as u32);
+
+        for &(row, col) in cells {
+            assert_eq!(game.matrix().get(row, col), Cell::Piece(*kind));
+        }
+    }
+
+    #[test]
+    fn test_soft_drop_scores_a_point_per_cell() {
+        let mut game = started(1);
+        let start = game.active().unwrap().position();
+
+        // 20x level 1 gravity: two rows every 100ms, so 525ms is ten and a half rows.
+        let mut inputs = pressed(Action::SoftDrop);
+        for _ in 0..5 {
+            game.step(&inputs, Duration::from_millis(105));
+            inputs = Inputs::default();
+            inputs.press(Action::SoftDrop, false);
+        }
+
+        assert_eq!(game.active().unwrap().position().i, start.i + 10);
+        assert_eq!(game.stats().score, 10);
+    }
+
+    #[test]
+    fn test_gravity_and_lock_delay_without_input() {
+        let mut game = Game::new(GameConfig::default(), 1);
+        let mut locked = false;
+
+        // Level 1 gravity is one row per second, plus the lock delay.
+        for _ in 0..(ROWS as usize + 2) * 1000 / 16 {
+            let events = game.step(&Inputs::default(), FRAME);
+            locked |= events
+                .iter()
+                .any(|event| matches!(event, Event::Locked { .. }));
+        }
+
+        assert!(locked);
+    }
+
+    #[test]
+    fn test_same_seed_same_game() {
+        let mut first = Game::new(GameConfig::default(), 7);
+        let mut second = Game::new(GameConfig::default(), 7);
+
+        for _ in 0..30 {
+            assert_eq!(
+                first.step(&pressed(Action::HardDrop), FRAME),
+                second.step(&pressed(Action::HardDrop), FRAME)
+            );
+        }
+    }
+
+    #[test]
+    fn test_spawn_centred_in_the_vanish_zone() {
+        for kind in PieceKind::ALL {
+            let table = &kind.definition().tables[0];
+            let position = spawn_position(table);
+            let cells = table_cells(table, &position);
+
+            let left = cells.iter().map(|&(_, col)| col).min().unwrap();
+            let right = cells.iter().map(|&(_, col)| col).max().unwrap();
+            let expected = match kind {
+                PieceKind::I => (3, 6),
+                PieceKind::O => (4, 5),
+                _ => (3, 5),
+            };
+            assert_eq!((left, right), expected, "{kind:?}");
+
+            let bottom = cells.iter().map(|&(row, _)| row).max().unwrap();
+            assert!(bottom < VANISH_ROWS as usize, "{kind:?}");
+        }
+
+        // Dropped a row straight away on an empty matrix.
+        let game = started(1);
+        assert_eq!(game.active().unwrap().position().i, VANISH_ROWS as i32 - 1);
+    }
+
+    #[test]
+    fn test_hold_swaps_once_per_piece() {
+        let mut game = started(3);
+        let first = game.active().unwrap().kind();
+
+        let events = game.step(&pressed(Action::Hold), FRAME);
+        assert_eq!(events[0], Event::Held(first));
+        assert_eq!(game.hold(), Some(first));
+
+        assert!(game.step(&pressed(Action::Hold), FRAME).is_empty());
+    }
+
+    #[test]
+    fn test_ultra_ends_after_two_minutes() {
+        let config = GameConfig {
+            mode: Mode::Ultra,
+            ..GameConfig::default()
+        };
+        let mut game = Game::new(config, 1);
+
+        // Left alone the pieces stack up in the middle, so skip ahead in one step.
+        game.step(&Inputs::default(), Duration::from_secs(119));
+        assert_eq!(game.ending(), None);
+
+        let events = game.step(&Inputs::default(), Duration::from_secs(1));
+        assert_eq!(events.last(), Some(&Event::Finished));
+        assert_eq!(game.ending(), Some(Ending::GoalReached));
+        assert_eq!(game.stats().time, Duration::from_secs(120));
+    }
+
+    #[test]
+    fn test_endless_runs_past_the_gravity_formula() {
+        // Endless has no level cap, and the guideline formula breaks down past level 115.
+        let config = GameConfig {
+            mode: Mode::Endless,
+            start_level: 200,
+            ..GameConfig::default()
+        };
+        let mut game = Game::new(config, 1);
+
+        for _ in 0..600 {
+            game.step(&Inputs::default(), FRAME);
+        }
+
+        assert_eq!(game.stats().level, 200);
+        assert!(game.stats().pieces > 0);
+    }
+
+    #[test]
+    fn test_block_out_ends_the_game() {
+        let mut game = started(1);
+
+        // The spawn rows are filled, so the next piece can't appear.
+        for col in 0..COLS as usize {
+            game.matrix
+                .set(VANISH_ROWS as usize - 1, col, Cell::Garbage);
+        }
+        game.spawn(PieceKind::T);
+
+        assert_eq!(game.events.last(), Some(&Event::GameOver));
+        assert_eq!(game.ending(), Some(Ending::ToppedOut));
+    }
+
+    #[test]
+    fn test_lock_out_ends_the_game() {
+        let mut game = started(1);
+
+        // A stack reaching the top of the visible matrix, with a gap so it doesn't clear.
+        for col in 0..COLS as usize - 1 {
+            game.matrix.set(VANISH_ROWS as usize, col, Cell::Garbage);
+        }
+        game.active.as_mut().unwrap().position.i = VANISH_ROWS as i32 - 2;
+
+        let events = game.step(&pressed(Action::HardDrop), FRAME);
+
+        // Ended by the lock itself, before a next piece could be blocked out.
+        assert!(matches!(
+            events[..],
+            [Event::Locked { .. }, Event::GameOver]
+        ));
+        assert_eq!(game.ending(), Some(Ending::ToppedOut));
+    }
+
+    #[test]
+    fn test_nothing_moves_after_hold_blocks_out() {
+        let mut game = started(1);
+
+        for col in 0..COLS as usize {
+            game.matrix
+                .set(VANISH_ROWS as usize - 1, col, Cell::Garbage);
+        }
+
+        let mut inputs = pressed(Action::Hold);
+        inputs.press(Action::HardDrop, true);
+        let events = game.step(&inputs, FRAME);
+
+        assert_eq!(events.last(), Some(&Event::GameOver));
+        assert!(
+            !events
+                .iter()
+                .any(|event| matches!(event, Event::Locked { .. }))
+        );
+    }
+
+    #[test]
+    fn test_moves_into_walls_and_blocks_are_rejected() {
+        let mut matrix = empty_matrix();
+        let table = Piece::new(PieceKind::T).get_table();
+
+        // Against the left wall, with a filled column right next to its other side.
+        let mut position = PieceIndicies { i: 10, j: 0 };
+        for row in 0..(ROWS + VANISH_ROWS) as usize {
+            matrix.set(row, 3, Cell::Garbage);
+        }
+
+        assert!(!try_move_piece(
+            &matrix,
+            table,
+            &mut position,
+            Direction::Left
+        ));
+        assert_eq!(position, PieceIndicies { i: 10, j: 0 });
+
+        assert!(!try_move_piece(
+            &matrix,
+            table,
+            &mut position,
+            Direction::Right
+        ));
+        assert_eq!(position, PieceIndicies { i: 10, j: 0 });
+    }
+
+    #[test]
+    fn test_rotation_kicks_off_the_wall() {
+        let mut matrix = empty_matrix();
+
+        // A T in state R with its stem against the left wall.
+        let mut piece = Piece::new(PieceKind::T);
+        piece.rotate_cw();
+        let mut active = ActivePiece {
+            piece,
+            position: PieceIndicies { i: 10, j: -1 },
+            last_kick: None,
+        };
+
+        // Blocks the (1, 0) kick, so the T has to take (1, -1): one right, one row down.
+        matrix.set(11, 2, Cell::Garbage);
+
+        let kick = try_rotate_piece(&matrix, &mut active, Rotation::Cw);
+
+        assert_eq!(kick, Some(2));
+        assert_eq!(active.rotation(), 2);
+        assert_eq!(active.position(), PieceIndicies { i: 11, j: 0 });
+    }
+
+    #[test]
+    fn test_rotation_without_a_fitting_kick_changes_nothing() {
+        let mut matrix = empty_matrix();
+        let mut active = ActivePiece {
+            piece: Piece::new(PieceKind::T),
+            position: PieceIndicies { i: 10, j: 3 },
+            last_kick: None,
+        };
+
+        // Everything but the cells the T is in now is filled.
+        let cells = table_cells(active.table(), &active.position);
+        for row in 0..(ROWS + VANISH_ROWS) as usize {
+            for col in 0..COLS as usize {
+                if !cells.contains(&(row, col)) {
+                    matrix.set(row, col, Cell::Garbage);
+                }
+            }
+        }
+
+        for rotation in [Rotation::Cw, Rotation::Ccw, Rotation::Half] {
+            assert_eq!(try_rotate_piece(&matrix, &mut active, rotation), None);
+            assert_eq!(active.rotation(), 0);
+            assert_eq!(active.position(), PieceIndicies { i: 10, j: 3 });
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use std::time::Duration;
+
+/// Shift distance meaning "all the way to the wall", used when ARR is 0.
+pub const TO_WALL: u32 = u32::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShiftDirection {
+    Left,
+    Right,
+}
+
+/// Delayed Auto Shift timings.
+#[derive(Debug, Clone, Copy)]
+pub struct Handling {
+    /// How long a direction has to be held before it starts repeating.
+    pub das: Duration,
+    /// Time between repeated shifts once DAS is charged. Zero shifts straight to the wall.
+    pub arr: Duration,
+    /// How long auto shift is held back after a new piece spawns. Zero disables it.
+    pub das_cut: Duration,
+}
+
+impl Default for Handling {
+    fn default() -> Self {
+        Handling {
+            das: Duration::from_millis(167),
+            arr: Duration::from_millis(33),
+            das_cut: Duration::ZERO,
+        }
+    }
+}
+
+/// State of the held horizontal direction. The DAS charge is kept when a new
+/// piece spawns, so holding a direction through a lock carries it over.
+#[derive(Default)]
+pub struct AutoShift {
+    direction: Option<ShiftDirection>,
+    held: Duration,
+    repeat: Duration,
+    cut: Duration,
+}
+
+impl AutoShift {
+    /// Feeds one frame of input. `*_pressed` is true on the frame a key goes
+    /// down, `*_held` while it stays down. Returns how many cells to shift,
+    /// `TO_WALL` meaning as far as possible.
+    pub fn update(
+        &mut self,
+        left_pressed: bool,
+        right_pressed: bool,
+        left_held: bool,
+        right_held: bool,
+        delta: Duration,
+        handling: &Handling,
+    ) -> Option<(ShiftDirection, u32)> {
+        // The most recently pressed direction wins.
+        if left_pressed || right_pressed {
+            let direction = if right_pressed {
+                ShiftDirection::Right
+            } else {
+                ShiftDirection::Left
+            };
+
+            self.direction = Some(direction);
+            self.held = Duration::ZERO;
+            self.repeat = Duration::ZERO;
+
+            return Some((direction, 1));
+        }
+
+        let still_held = |direction| match direction {
+            ShiftDirection::Left => left_held,
+            ShiftDirection::Right => right_held,
+        };
+
+        match self.direction {
+            Some(direction) if still_held(direction) => {}
+            _ => {
+                self.direction = [ShiftDirection::Left, ShiftDirection::Right]
+                    .into_iter()
+                    .find(|&direction| still_held(direction));
+                self.held = Duration::ZERO;
+                self.repeat = Duration::ZERO;
+                return None;
+            }
+        }
+
+        let direction = self.direction?;
+
+        let delta = if self.cut > Duration::ZERO {
+            let used = self.cut.min(delta);
+            self.cut -= used;
+            delta - used
+        } else {
+            delta
+        };
+
+        let was_charged = self.held >= handling.das;
+        self.held += delta;
+
+        if self.held < handling.das {
+            return None;
+        }
+
+        if handling.arr.is_zero() {
+            return Some((direction, TO_WALL));
+        }
+
+        let mut cells = 0;
+
+        if was_charged {
+            self.repeat += delta;
+        } else {
+            // The first auto shift happens the moment DAS charges.
+            cells += 1;
+            self.repeat = self.held - handling.das;
+        }
+
+        while self.repeat >= handling.arr {
+            self.repeat -= handling.arr;
+            cells += 1;
+        }
+
+        (cells > 0).then_some((direction, cells))
+    }
+
+    /// Call when a new piece appears.
+    pub fn on_spawn(&mut self, handling: &Handling) {
+        self.cut = handling.das_cut;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::handling::{AutoShift, Handling, ShiftDirection, TO_WALL};
+
+    const HANDLING: Handling = Handling {
+        das: Duration::from_millis(100),
+        arr: Duration::from_millis(20),
+        das_cut: Duration::ZERO,
+    };
+
+    fn hold_left(auto_shift: &mut AutoShift, ms: u64, handling: &Handling) -> Option<u32> {
+        auto_shift
+            .update(
+                false,
+                false,
+                true,
+                false,
+                Duration::from_millis(ms),
+                handling,
+            )
+            .map(|(_, cells)| cells)
+    }
+
+    #[test]
+    fn test_tap_then_auto_repeat() {
+        let mut auto_shift = AutoShift::default();
+
+        let tap = auto_shift.update(true, false, true, false, Duration::ZERO, &HANDLING);
+        assert_eq!(tap, Some((ShiftDirection::Left, 1)));
+
+        assert_eq!(hold_left(&mut auto_shift, 90, &HANDLING), None);
+        assert_eq!(hold_left(&mut auto_shift, 10, &HANDLING), Some(1));
+        assert_eq!(hold_left(&mut auto_shift, 10, &HANDLING), None);
+        assert_eq!(hold_left(&mut auto_shift, 50, &HANDLING), Some(3));
+    }
+
+    #[test]
+    fn test_zero_arr_goes_to_wall() {
+        let handling = Handling {
+            arr: Duration::ZERO,
+            ..HANDLING
+        };
+        let mut auto_shift = AutoShift::default();
+
+        auto_shift.update(true, false, true, false, Duration::ZERO, &handling);
+
+        assert_eq!(hold_left(&mut auto_shift, 100, &handling), Some(TO_WALL));
+    }
+
+    #[test]
+    fn test_charge_carries_over_unless_cut() {
+        let handling = Handling {
+            das_cut: Duration::from_millis(50),
+            ..HANDLING
+        };
+        let mut auto_shift = AutoShift::default();
+
+        auto_shift.update(true, false, true, false, Duration::ZERO, &HANDLING);
+        hold_left(&mut auto_shift, 100, &HANDLING);
+
+        auto_shift.on_spawn(&HANDLING);
+        assert_eq!(hold_left(&mut auto_shift, 20, &HANDLING), Some(1));
+
+        auto_shift.on_spawn(&handling);
+        assert_eq!(hold_left(&mut auto_shift, 40, &handling), None);
+        assert_eq!(hold_left(&mut auto_shift, 30, &handling), Some(1));
+    }
+}
@@ -0,0 +1,215 @@
+use std::{collections::HashMap, fs, io, path::PathBuf, time::Duration};
+
+use bevy::ecs::resource::Resource;
+
+use crate::engine::{Ending, Mode, Stats};
+
+/// How many results are kept per mode.
+pub const KEPT: usize = 5;
+
+/// One finished game in the high score table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub score: u32,
+    pub lines: u32,
+    pub level: u32,
+    pub time: Duration,
+}
+
+impl Entry {
+    pub fn new(stats: &Stats) -> Self {
+        Entry {
+            score: stats.score,
+            lines: stats.lines,
+            level: stats.level,
+            time: stats.time,
+        }
+    }
+}
+
+/// Best results per mode, kept in the user data directory.
+#[derive(Resource, Default, Debug)]
+pub struct HighScores {
+    entries: HashMap<Mode, Vec<Entry>>,
+}
+
+impl HighScores {
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("tetris").join("high_scores.toml"))
+    }
+
+    /// Reads the table from the user data directory, starting empty if
+    /// there isn't one or it can't be read.
+    pub fn load() -> Self {
+        let Some(path) = HighScores::path() else {
+            return HighScores::default();
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(text) => HighScores::parse(&text).unwrap_or_else(|err| {
+                eprintln!("Invalid high scores in {}: {err}", path.display());
+                HighScores::default()
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => HighScores::default(),
+            Err(err) => {
+                eprintln!("Can't read {}: {err}", path.display());
+                HighScores::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let Some(path) = HighScores::path() else {
+            return;
+        };
+
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&path, self.to_toml()));
+
+        if let Err(err) = result {
+            eprintln!("Can't save high scores to {}: {err}", path.display());
+        }
+    }
+
+    /// Parses `[[mode]]` tables of `score`, `lines`, `level` and `time_ms`.
+    /// Unknown modes and incomplete entries are skipped.
+    pub fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        let table: toml::Table = text.parse()?;
+        let mut high_scores = HighScores::default();
+
+        for (name, value) in table {
+            let (Some(mode), toml::Value::Array(entries)) = (Mode::parse(&name), value) else {
+                continue;
+            };
+
+            for entry in entries.iter().filter_map(parse_entry) {
+                high_scores.insert(mode, entry);
+            }
+        }
+
+        Ok(high_scores)
+    }
+
+    fn to_toml(&self) -> String {
+        let mut table = toml::Table::new();
+
+        for (mode, entries) in &self.entries {
+            let entries = entries
+                .iter()
+                .map(|entry| {
+                    let mut table = toml::Table::new();
+                    table.insert("score".into(), i64::from(entry.score).into());
+                    table.insert("lines".into(), i64::from(entry.lines).into());
+                    table.insert("level".into(), i64::from(entry.level).into());
+                    table.insert("time_ms".into(), (entry.time.as_millis() as i64).into());
+                    toml::Value::Table(table)
+                })
+                .collect();
+
+            table.insert(mode.name().into(), toml::Value::Array(entries));
+        }
+
+        table.to_string()
+    }
+
+    pub fn get(&self, mode: Mode) -> &[Entry] {
+        self.entries
+            .get(&mode)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Adds a finished game, returning its place in the table if it made it.
+    /// Sprint only counts games that reached the goal.
+    pub fn record(&mut self, mode: Mode, entry: Entry, ending: Ending) -> Option<usize> {
+        if mode == Mode::Sprint && ending != Ending::GoalReached {
+            return None;
+        }
+
+        self.insert(mode, entry)
+    }
+
+    fn insert(&mut self, mode: Mode, entry: Entry) -> Option<usize> {
+        let entries = self.entries.entry(mode).or_default();
+        let place = entries
+            .iter()
+            .position(|other| ranks_above(mode, &entry, other))
+            .unwrap_or(entries.len());
+
+        if place >= KEPT {
+            return None;
+        }
+
+        entries.insert(place, entry);
+        entries.truncate(KEPT);
+        Some(place)
+    }
+}
+
+/// Sprint is ranked by time, every other mode by score.
+fn ranks_above(mode: Mode, entry: &Entry, other: &Entry) -> bool {
+    match mode {
+        Mode::Sprint => entry.time < other.time,
+        _ => entry.score > other.score,
+    }
+}
+
+fn parse_entry(value: &toml::Value) -> Option<Entry> {
+    let field = |name: &str| value.get(name)?.as_integer();
+
+    Some(Entry {
+        score: field("score")?.try_into().ok()?,
+        lines: field("lines")?.try_into().ok()?,
+        level: field("level")?.try_into().ok()?,
+        time: Duration::from_millis(field("time_ms")?.try_into().ok()?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        engine::{Ending, Mode},
+        high_scores::{Entry, HighScores, KEPT},
+    };
+
+    fn entry(score: u32, seconds: u64) -> Entry {
+        Entry {
+            score,
+            lines: 40,
+            level: 5,
+            time: Duration::from_secs(seconds),
+        }
+    }
+
+    #[test]
+    fn test_ranking_and_round_trip() {
+        let mut high_scores = HighScores::default();
+
+        for score in 0..KEPT as u32 {
+            high_scores.record(Mode::Marathon, entry(score * 100, 60), Ending::ToppedOut);
+        }
+        assert_eq!(
+            high_scores.record(Mode::Marathon, entry(250, 60), Ending::ToppedOut),
+            Some(2)
+        );
+        assert_eq!(
+            high_scores.record(Mode::Marathon, entry(0, 60), Ending::ToppedOut),
+            None
+        );
+        assert_eq!(high_scores.get(Mode::Marathon).len(), KEPT);
+
+        // Sprint keeps the fastest finished runs only.
+        high_scores.record(Mode::Sprint, entry(0, 90), Ending::GoalReached);
+        high_scores.record(Mode::Sprint, entry(0, 60), Ending::GoalReached);
+        high_scores.record(Mode::Sprint, entry(0, 30), Ending::ToppedOut);
+        assert_eq!(high_scores.get(Mode::Sprint), [entry(0, 60), entry(0, 90)]);
+
+        let parsed = HighScores::parse(&high_scores.to_toml()).unwrap();
+        assert_eq!(parsed.get(Mode::Marathon), high_scores.get(Mode::Marathon));
+        assert_eq!(parsed.get(Mode::Sprint), high_scores.get(Mode::Sprint));
+    }
+}
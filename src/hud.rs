@@ -0,0 +1,166 @@
+use std::{collections::HashSet, time::Duration};
+
+use bevy::prelude::*;
+
+use crate::{
+    consts::{SIDE_PANEL_WIDTH, TILE_SIZE},
+    engine::{Mode, Stats},
+};
+
+/// A line of the HUD. Each one can be hidden from the settings menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HudStat {
+    Score,
+    Level,
+    Lines,
+    Time,
+    /// Pieces per second.
+    Pps,
+    InputsPerPiece,
+    /// Attack per minute, only shown in modes that count attack.
+    Apm,
+}
+
+impl HudStat {
+    pub const ALL: [HudStat; 7] = [
+        HudStat::Score,
+        HudStat::Level,
+        HudStat::Lines,
+        HudStat::Time,
+        HudStat::Pps,
+        HudStat::InputsPerPiece,
+        HudStat::Apm,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HudStat::Score => "Score",
+            HudStat::Level => "Level",
+            HudStat::Lines => "Lines",
+            HudStat::Time => "Time",
+            HudStat::Pps => "PPS",
+            HudStat::InputsPerPiece => "Inputs/piece",
+            HudStat::Apm => "APM",
+        }
+    }
+
+    fn value(self, stats: &Stats, mode: Mode) -> Option<String> {
+        let seconds = stats.time.as_secs_f64();
+        let per_piece = |count: u32| {
+            if stats.pieces == 0 {
+                0.0
+            } else {
+                f64::from(count) / f64::from(stats.pieces)
+            }
+        };
+        let per_second = |count: u32| {
+            if seconds == 0.0 {
+                0.0
+            } else {
+                f64::from(count) / seconds
+            }
+        };
+
+        let value = match self {
+            HudStat::Score => stats.score.to_string(),
+            HudStat::Level => stats.level.to_string(),
+            HudStat::Lines => match mode.line_goal() {
+                Some(goal) => format!("{}/{goal}", stats.lines),
+                None => stats.lines.to_string(),
+            },
+            HudStat::Time => format_time(stats.time),
+            HudStat::Pps => format!("{:.2}", per_second(stats.pieces)),
+            HudStat::InputsPerPiece => format!("{:.2}", per_piece(stats.inputs)),
+            HudStat::Apm if mode.counts_attack() => {
+                format!("{:.1}", per_second(stats.attack) * 60.0)
+            }
+            HudStat::Apm => return None,
+        };
+
+        Some(value)
+    }
+}
+
+/// The HUD lines for `stats`, leaving out those not in `shown`.
+pub fn hud_text(stats: &Stats, mode: Mode, shown: &HashSet<HudStat>) -> String {
+    HudStat::ALL
+        .into_iter()
+        .filter(|stat| shown.contains(stat))
+        .filter_map(|stat| Some(format!("{}: {}", stat.name(), stat.value(stats, mode)?)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Formats a duration as `m:ss.cc`.
+pub fn format_time(time: Duration) -> String {
+    let centis = time.as_millis() / 10;
+    format!(
+        "{}:{:02}.{:02}",
+        centis / 6000,
+        centis / 100 % 60,
+        centis % 100
+    )
+}
+
+/// Text at the bottom of the side panel, filled in by `update_hud`.
+#[derive(Component)]
+pub struct Hud;
+
+pub fn hud() -> impl Bundle {
+    (
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(0.0),
+            bottom: Val::Px(TILE_SIZE),
+            width: Val::Px(SIDE_PANEL_WIDTH),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        children![(
+            Text::default(),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextLayout::new_with_justify(Justify::Center),
+            Hud,
+        )],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, time::Duration};
+
+    use crate::{
+        engine::{Mode, Stats},
+        hud::{HudStat, hud_text},
+    };
+
+    #[test]
+    fn test_hud_text() {
+        let stats = Stats {
+            score: 1200,
+            level: 2,
+            lines: 12,
+            pieces: 30,
+            inputs: 90,
+            attack: 8,
+            time: Duration::from_secs(60),
+        };
+        let shown = HashSet::from([HudStat::Lines, HudStat::Pps, HudStat::Apm]);
+
+        assert_eq!(
+            hud_text(&stats, Mode::Sprint, &shown),
+            "Lines: 12/40\nPPS: 0.50"
+        );
+        assert_eq!(
+            hud_text(&stats, Mode::Endless, &shown),
+            "Lines: 12\nPPS: 0.50\nAPM: 8.0"
+        );
+
+        let fresh = Stats::default();
+        let all = HashSet::from(HudStat::ALL);
+        assert!(hud_text(&fresh, Mode::Ultra, &all).contains("Inputs/piece: 0.00"));
+    }
+}
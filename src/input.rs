@@ -0,0 +1,459 @@
+use std::{collections::HashMap, fmt::Display, fs, io, path::PathBuf};
+
+use bevy::{
+    ecs::{
+        resource::Resource,
+        system::{Query, Res, ResMut},
+    },
+    input::{
+        ButtonInput,
+        gamepad::{Gamepad, GamepadButton},
+        keyboard::KeyCode,
+    },
+    math::Vec2,
+};
+
+use crate::engine::Action;
+
+impl Action {
+    fn default_keys(self) -> &'static [KeyCode] {
+        match self {
+            Action::MoveLeft => &[KeyCode::KeyH, KeyCode::ArrowLeft],
+            Action::MoveRight => &[KeyCode::KeyL, KeyCode::ArrowRight],
+            Action::SoftDrop => &[KeyCode::KeyJ, KeyCode::ArrowDown],
+            Action::HardDrop => &[KeyCode::Space],
+            Action::RotateCw => &[KeyCode::KeyX, KeyCode::ArrowUp],
+            Action::RotateCcw => &[KeyCode::KeyZ],
+            Action::Rotate180 => &[KeyCode::KeyA],
+            Action::Hold => &[KeyCode::KeyC, KeyCode::ShiftLeft],
+            Action::Pause => &[KeyCode::KeyP, KeyCode::Escape],
+            Action::Restart => &[KeyCode::KeyR],
+        }
+    }
+
+    fn default_buttons(self) -> &'static [GamepadButton] {
+        match self {
+            Action::MoveLeft => &[GamepadButton::DPadLeft],
+            Action::MoveRight => &[GamepadButton::DPadRight],
+            Action::SoftDrop => &[GamepadButton::DPadDown],
+            Action::HardDrop => &[GamepadButton::DPadUp, GamepadButton::RightTrigger],
+            Action::RotateCw => &[GamepadButton::East],
+            Action::RotateCcw => &[GamepadButton::South],
+            Action::Rotate180 => &[GamepadButton::North],
+            Action::Hold => &[GamepadButton::LeftTrigger],
+            Action::Pause => &[GamepadButton::Start],
+            Action::Restart => &[GamepadButton::Select],
+        }
+    }
+}
+
+/// Moving through menus. These keep fixed controls so that a broken
+/// bindings file can't lock the player out of the menus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MenuAction {
+    Up,
+    Down,
+    /// Changes the value of the selected item, if it has one.
+    Left,
+    Right,
+    Confirm,
+    Back,
+}
+
+impl MenuAction {
+    pub const ALL: [MenuAction; 6] = [
+        MenuAction::Up,
+        MenuAction::Down,
+        MenuAction::Left,
+        MenuAction::Right,
+        MenuAction::Confirm,
+        MenuAction::Back,
+    ];
+
+    fn keys(self) -> &'static [KeyCode] {
+        match self {
+            MenuAction::Up => &[KeyCode::ArrowUp, KeyCode::KeyK],
+            MenuAction::Down => &[KeyCode::ArrowDown, KeyCode::KeyJ],
+            MenuAction::Left => &[KeyCode::ArrowLeft, KeyCode::KeyH],
+            MenuAction::Right => &[KeyCode::ArrowRight, KeyCode::KeyL],
+            MenuAction::Confirm => &[KeyCode::Enter, KeyCode::Space],
+            MenuAction::Back => &[KeyCode::Escape, KeyCode::Backspace],
+        }
+    }
+
+    fn buttons(self) -> &'static [GamepadButton] {
+        match self {
+            MenuAction::Up => &[GamepadButton::DPadUp],
+            MenuAction::Down => &[GamepadButton::DPadDown],
+            MenuAction::Left => &[GamepadButton::DPadLeft],
+            MenuAction::Right => &[GamepadButton::DPadRight],
+            MenuAction::Confirm => &[GamepadButton::South],
+            MenuAction::Back => &[GamepadButton::East],
+        }
+    }
+}
+
+/// Stick deflection below which the left stick is treated as centred.
+pub const DEFAULT_DEAD_ZONE: f32 = 0.5;
+
+#[derive(Debug)]
+pub enum BindingsError {
+    Io(PathBuf, io::Error),
+    Toml(toml::de::Error),
+    UnknownAction(String),
+    NotAKeyList(String),
+    UnknownKey { action: String, key: String },
+    UnknownButton { action: String, button: String },
+    InvalidDeadZone,
+}
+
+impl Display for BindingsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BindingsError::Io(path, err) => write!(f, "can't read {}: {err}", path.display()),
+            BindingsError::Toml(err) => write!(f, "{err}"),
+            BindingsError::UnknownAction(action) => {
+                let known: Vec<&str> = Action::ALL.iter().map(|action| action.name()).collect();
+                write!(
+                    f,
+                    "unknown action `{action}`, expected one of {}",
+                    known.join(", ")
+                )
+            }
+            BindingsError::NotAKeyList(action) => {
+                write!(f, "`{action}` should be a key name or a list of key names")
+            }
+            BindingsError::UnknownKey { action, key } => {
+                write!(f, "unknown key `{key}` bound to `{action}`")
+            }
+            BindingsError::UnknownButton { action, button } => {
+                write!(f, "unknown gamepad button `{button}` bound to `{action}`")
+            }
+            BindingsError::InvalidDeadZone => {
+                write!(f, "`gamepad.dead_zone` should be a number between 0 and 1")
+            }
+        }
+    }
+}
+
+/// Which keys and gamepad buttons trigger which action.
+#[derive(Resource)]
+pub struct Bindings {
+    keys: HashMap<Action, Vec<KeyCode>>,
+    buttons: HashMap<Action, Vec<GamepadButton>>,
+    dead_zone: f32,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings {
+            keys: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_keys().to_vec()))
+                .collect(),
+            buttons: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_buttons().to_vec()))
+                .collect(),
+            dead_zone: DEFAULT_DEAD_ZONE,
+        }
+    }
+}
+
+impl Bindings {
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("tetris").join("bindings.toml"))
+    }
+
+    /// Reads the bindings file from the user config directory. Falls back to
+    /// the defaults, with a message, if it exists but can't be used.
+    pub fn load() -> Self {
+        let Some(path) = Bindings::path() else {
+            return Bindings::default();
+        };
+
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Bindings::default();
+            }
+            Err(err) => {
+                eprintln!("{}, using default bindings", BindingsError::Io(path, err));
+                return Bindings::default();
+            }
+        };
+
+        Bindings::parse(&text).unwrap_or_else(|err| {
+            eprintln!(
+                "Invalid bindings in {}: {err}, using defaults",
+                path.display()
+            );
+            Bindings::default()
+        })
+    }
+
+    /// Parses a TOML file of `action = ["Key", ...]` entries, with gamepad
+    /// buttons and the stick dead zone under a `[gamepad]` table.
+    /// Actions that aren't listed keep their default bindings.
+    pub fn parse(text: &str) -> Result<Self, BindingsError> {
+        let table: toml::Table = text.parse().map_err(BindingsError::Toml)?;
+        let mut bindings = Bindings::default();
+
+        for (name, value) in table {
+            if name == "gamepad"
+                && let toml::Value::Table(gamepad) = value
+            {
+                bindings.parse_gamepad(gamepad)?;
+                continue;
+            }
+
+            let action = find_action(&name)?;
+            let keys = parse_names(&name, value, parse_key, |action, key| {
+                BindingsError::UnknownKey { action, key }
+            })?;
+
+            bindings.keys.insert(action, keys);
+        }
+
+        Ok(bindings)
+    }
+
+    fn parse_gamepad(&mut self, table: toml::Table) -> Result<(), BindingsError> {
+        for (name, value) in table {
+            if name == "dead_zone" {
+                self.dead_zone = match value {
+                    toml::Value::Float(dead_zone) => dead_zone as f32,
+                    toml::Value::Integer(dead_zone) => dead_zone as f32,
+                    _ => return Err(BindingsError::InvalidDeadZone),
+                };
+
+                if !(0.0..1.0).contains(&self.dead_zone) {
+                    return Err(BindingsError::InvalidDeadZone);
+                }
+                continue;
+            }
+
+            let action = find_action(&name)?;
+            let buttons = parse_names(&name, value, parse_button, |action, button| {
+                BindingsError::UnknownButton { action, button }
+            })?;
+
+            self.buttons.insert(action, buttons);
+        }
+
+        Ok(())
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.keys
+            .get(&action)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn buttons(&self, action: Action) -> &[GamepadButton] {
+        self.buttons
+            .get(&action)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Whether the left stick counts as pushed towards the action.
+    /// Only movement and soft drop are on the stick.
+    fn stick_pressed(&self, action: Action, stick: Vec2) -> bool {
+        match action {
+            Action::MoveLeft => stick.x < -self.dead_zone,
+            Action::MoveRight => stick.x > self.dead_zone,
+            Action::SoftDrop => stick.y < -self.dead_zone,
+            _ => false,
+        }
+    }
+}
+
+fn find_action(name: &str) -> Result<Action, BindingsError> {
+    Action::ALL
+        .into_iter()
+        .find(|action| action.name() == name)
+        .ok_or_else(|| BindingsError::UnknownAction(name.to_owned()))
+}
+
+/// Parses a single name or a list of names bound to the action `action`.
+fn parse_names<T>(
+    action: &str,
+    value: toml::Value,
+    parse: fn(&str) -> Option<T>,
+    unknown: fn(String, String) -> BindingsError,
+) -> Result<Vec<T>, BindingsError> {
+    let names = match value {
+        toml::Value::String(name) => vec![toml::Value::String(name)],
+        toml::Value::Array(names) => names,
+        _ => return Err(BindingsError::NotAKeyList(action.to_owned())),
+    };
+
+    names
+        .into_iter()
+        .map(|name| match name {
+            toml::Value::String(name) => {
+                parse(&name).ok_or_else(|| unknown(action.to_owned(), name))
+            }
+            _ => Err(BindingsError::NotAKeyList(action.to_owned())),
+        })
+        .collect()
+}
+
+/// Mirrors the keyboard and every connected gamepad into
+/// `ButtonInput<Action>` through the bindings.
+pub fn update_actions(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    bindings: Res<Bindings>,
+    mut actions: ResMut<ButtonInput<Action>>,
+) {
+    actions.clear();
+
+    for action in Action::ALL {
+        let pressed = keyboard.any_pressed(bindings.keys(action).iter().copied())
+            || gamepads.iter().any(|gamepad| {
+                gamepad.any_pressed(bindings.buttons(action).iter().copied())
+                    || bindings.stick_pressed(action, gamepad.left_stick())
+            });
+
+        if pressed {
+            actions.press(action);
+        } else {
+            actions.release(action);
+        }
+    }
+}
+
+/// Mirrors the keyboard and every connected gamepad into
+/// `ButtonInput<MenuAction>`, the left stick included.
+pub fn update_menu_actions(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    bindings: Res<Bindings>,
+    mut actions: ResMut<ButtonInput<MenuAction>>,
+) {
+    actions.clear();
+
+    for action in MenuAction::ALL {
+        let pressed = keyboard.any_pressed(action.keys().iter().copied())
+            || gamepads.iter().any(|gamepad| {
+                let stick = gamepad.left_stick();
+                gamepad.any_pressed(action.buttons().iter().copied())
+                    || match action {
+                        MenuAction::Up => stick.y > bindings.dead_zone,
+                        MenuAction::Down => stick.y < -bindings.dead_zone,
+                        MenuAction::Left => stick.x < -bindings.dead_zone,
+                        MenuAction::Right => stick.x > bindings.dead_zone,
+                        MenuAction::Confirm | MenuAction::Back => false,
+                    }
+            });
+
+        if pressed {
+            actions.press(action);
+        } else {
+            actions.release(action);
+        }
+    }
+}
+
+macro_rules! variant_names {
+    ($(#[$doc:meta])* fn $parse:ident -> $ty:ident { $($variant:ident),* $(,)? }) => {
+        $(#[$doc])*
+        fn $parse(name: &str) -> Option<$ty> {
+            match name {
+                $(stringify!($variant) => Some($ty::$variant),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+variant_names!(
+    /// Parses a key by its `KeyCode` variant name, e.g. `KeyA` or `ArrowLeft`.
+    fn parse_key -> KeyCode {
+        KeyA, KeyB, KeyC, KeyD, KeyE, KeyF, KeyG, KeyH, KeyI, KeyJ, KeyK, KeyL, KeyM, KeyN, KeyO,
+        KeyP, KeyQ, KeyR, KeyS, KeyT, KeyU, KeyV, KeyW, KeyX, KeyY, KeyZ, Digit0, Digit1, Digit2,
+        Digit3, Digit4, Digit5, Digit6, Digit7, Digit8, Digit9, Numpad0, Numpad1, Numpad2,
+        Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9, ArrowLeft, ArrowRight,
+        ArrowUp, ArrowDown, Space, Enter, Escape, Tab, Backspace, ShiftLeft, ShiftRight,
+        ControlLeft, ControlRight, AltLeft, AltRight, Comma, Period, Slash, Semicolon, Quote,
+        BracketLeft, BracketRight, Minus, Equal, Backquote, Backslash, F1, F2, F3, F4, F5, F6, F7,
+        F8, F9, F10, F11, F12,
+    }
+);
+
+variant_names!(
+    /// Parses a button by its `GamepadButton` variant name, e.g. `South` or `DPadLeft`.
+    fn parse_button -> GamepadButton {
+        South, East, North, West, C, Z, LeftTrigger, LeftTrigger2, RightTrigger, RightTrigger2,
+        Select, Start, Mode, LeftThumb, RightThumb, DPadUp, DPadDown, DPadLeft, DPadRight,
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        input::{gamepad::GamepadButton, keyboard::KeyCode},
+        math::Vec2,
+    };
+
+    use crate::{
+        engine::Action,
+        input::{Bindings, BindingsError},
+    };
+
+    #[test]
+    fn test_missing_actions_keep_defaults() {
+        let bindings = Bindings::parse(
+            r#"
+            left = ["KeyA", "Numpad4"]
+            hold = "KeyV"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            bindings.keys(Action::MoveLeft),
+            [KeyCode::KeyA, KeyCode::Numpad4]
+        );
+        assert_eq!(bindings.keys(Action::Hold), [KeyCode::KeyV]);
+        assert_eq!(
+            bindings.keys(Action::HardDrop),
+            Action::HardDrop.default_keys()
+        );
+    }
+
+    #[test]
+    fn test_errors_name_the_bad_entry() {
+        let err = Bindings::parse(r#"left = ["KeyA", "Lft"]"#).err().unwrap();
+        assert!(matches!(err, BindingsError::UnknownKey { .. }));
+        assert!(err.to_string().contains("`Lft`"));
+
+        let err = Bindings::parse(r#"jump = "Space""#).err().unwrap();
+        assert!(err.to_string().contains("`jump`"));
+    }
+
+    #[test]
+    fn test_gamepad_table() {
+        let bindings = Bindings::parse(
+            r#"
+            [gamepad]
+            dead_zone = 0.25
+            hold = ["West", "LeftTrigger"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            bindings.buttons(Action::Hold),
+            [GamepadButton::West, GamepadButton::LeftTrigger]
+        );
+        assert!(bindings.stick_pressed(Action::MoveLeft, Vec2::new(-0.3, 0.0)));
+        assert!(!bindings.stick_pressed(Action::MoveRight, Vec2::new(0.2, 0.0)));
+
+        let err = Bindings::parse("[gamepad]\ndead_zone = 2.0").err().unwrap();
+        assert!(matches!(err, BindingsError::InvalidDeadZone));
+    }
+}
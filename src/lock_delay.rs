@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+/// How many moves or rotations may reset the timer under `LockReset::Extended`
+/// before the piece has to reach a new lowest row.
+pub const MAX_LOCK_RESETS: u32 = 15;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LockReset {
+    /// Moves and rotations reset the timer, at most `MAX_LOCK_RESETS` times.
+    #[default]
+    Extended,
+    /// Moves and rotations always reset the timer.
+    Infinite,
+    /// Only falling to a new lowest row resets the timer.
+    Step,
+}
+
+impl LockReset {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "extended" => Some(LockReset::Extended),
+            "infinite" => Some(LockReset::Infinite),
+            "step" => Some(LockReset::Step),
+            _ => None,
+        }
+    }
+
+    /// The name `parse` accepts.
+    pub fn name(self) -> &'static str {
+        match self {
+            LockReset::Extended => "extended",
+            LockReset::Infinite => "infinite",
+            LockReset::Step => "step",
+        }
+    }
+}
+
+/// Lock delay of the active piece. Replaced whenever a new piece spawns.
+pub struct LockDelay {
+    mode: LockReset,
+    delay: Duration,
+    elapsed: Duration,
+    resets: u32,
+    lowest_row: i32,
+    grounded: bool,
+}
+
+impl LockDelay {
+    pub fn new(mode: LockReset, delay: Duration) -> Self {
+        LockDelay {
+            mode,
+            delay,
+            elapsed: Duration::ZERO,
+            resets: 0,
+            lowest_row: i32::MIN,
+            grounded: false,
+        }
+    }
+
+    /// Advances the timer by `delta` while the piece rests on something.
+    /// Returns whether the piece should lock now.
+    pub fn update(&mut self, row: i32, grounded: bool, delta: Duration) -> bool {
+        if row > self.lowest_row {
+            self.lowest_row = row;
+            self.resets = 0;
+            self.elapsed = Duration::ZERO;
+        }
+
+        self.grounded = grounded;
+
+        if !grounded {
+            return false;
+        }
+
+        self.elapsed += delta;
+
+        let out_of_resets = self.mode == LockReset::Extended && self.resets >= MAX_LOCK_RESETS;
+
+        self.elapsed >= self.delay || out_of_resets
+    }
+
+    /// Call after every successful move or rotation of the piece.
+    pub fn on_move(&mut self) {
+        match self.mode {
+            LockReset::Extended => {
+                if self.grounded && self.resets < MAX_LOCK_RESETS {
+                    self.elapsed = Duration::ZERO;
+                    self.resets += 1;
+                }
+            }
+            LockReset::Infinite => self.elapsed = Duration::ZERO,
+            LockReset::Step => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::lock_delay::{LockDelay, LockReset, MAX_LOCK_RESETS};
+
+    const DELAY: Duration = Duration::from_millis(500);
+    const FRAME: Duration = Duration::from_millis(300);
+
+    #[test]
+    fn test_locks_after_delay() {
+        let mut lock_delay = LockDelay::new(LockReset::Extended, DELAY);
+
+        assert!(!lock_delay.update(5, false, FRAME));
+        assert!(!lock_delay.update(5, true, FRAME));
+        assert!(lock_delay.update(5, true, FRAME));
+    }
+
+    #[test]
+    fn test_extended_runs_out_of_resets() {
+        let mut lock_delay = LockDelay::new(LockReset::Extended, DELAY);
+
+        for _ in 0..MAX_LOCK_RESETS {
+            assert!(!lock_delay.update(5, true, FRAME));
+            lock_delay.on_move();
+        }
+
+        assert!(lock_delay.update(5, true, Duration::ZERO));
+
+        // Reaching a new lowest row gives the resets back.
+        assert!(!lock_delay.update(6, true, Duration::ZERO));
+    }
+
+    #[test]
+    fn test_step_ignores_moves() {
+        let mut lock_delay = LockDelay::new(LockReset::Step, DELAY);
+
+        assert!(!lock_delay.update(5, true, FRAME));
+        lock_delay.on_move();
+        assert!(lock_delay.update(5, true, FRAME));
+    }
+
+    #[test]
+    fn test_infinite_never_runs_out() {
+        let mut lock_delay = LockDelay::new(LockReset::Infinite, DELAY);
+
+        for _ in 0..MAX_LOCK_RESETS * 4 {
+            assert!(!lock_delay.update(5, true, FRAME));
+            lock_delay.on_move();
+        }
+    }
+}
@@ -1,278 +1,995 @@
-use bevy::{prelude::*, window::WindowResolution};
+// Bevy systems routinely take many parameters and nested query filters.
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+use bevy::{app::AppExit, input::InputSystems, prelude::*, window::WindowResolution};
 
 mod consts;
+mod engine;
+mod handling;
+mod high_scores;
+mod hud;
+mod input;
+mod lock_delay;
 mod matrix;
+mod menu;
 mod pieces;
+mod randomizer;
+mod render;
+mod replay;
+mod scoring;
+mod settings;
+mod tspin;
 
 use pieces::*;
 
 use crate::{
-    consts::{COLS, FALL_SPEED_UP, ROWS, TILE_SIZE},
-    matrix::{Matrix, check_for_colision, fix_piece},
+    consts::{
+        COLS, MAX_START_LEVEL, PREVIEW_SCALE, ROWS, SIDE_PANEL_WIDTH, TILE_SIZE, VANISH_ROWS,
+    },
+    engine::{Action, Ending, Event, Game, GameConfig, Inputs, Mode},
+    high_scores::{Entry, HighScores},
+    hud::{Hud, format_time, hud, hud_text},
+    input::{Bindings, MenuAction, update_actions, update_menu_actions},
+    matrix::{Cell, Matrix},
+    menu::{Menu, MenuChosen, MenuItem, cycle, draw_menus, menu_text, navigate_menus, overlay},
+    render::{MeshCache, Palette, block_translation, piece_translation},
+    replay::{Replay, Replays},
+    settings::Settings,
+    tspin::TSpin,
 };
 use rand::prelude::*;
+use std::collections::HashMap;
 
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins.set(get_window_settings()))
+        .insert_resource(Settings::from_args(std::env::args().skip(1)))
+        .insert_resource(Bindings::load())
+        .insert_resource(HighScores::load())
+        .insert_resource(Replays::load())
+        .init_resource::<ButtonInput<Action>>()
+        .init_resource::<ButtonInput<MenuAction>>()
+        .init_state::<GameState>()
+        .add_message::<GameEvent>()
+        .add_message::<MenuChosen>()
         .add_systems(Startup, setup)
-        .add_systems(Update, handle_input)
-        .add_systems(Update, (advance_timer, apply_gravity).chain())
-        // .add_systems(Update, check_for_collision)
-        // .add_systems(Update, bounds)
-        .add_systems(Update, create_piece)
-        // .add_systems(Update, update_random_field)
+        .add_systems(
+            PreUpdate,
+            (update_actions, update_menu_actions).after(InputSystems),
+        )
+        .add_systems(OnEnter(GameState::MainMenu), show_main_menu)
+        .add_systems(OnEnter(GameState::Countdown), show_countdown)
+        .add_systems(Update, run_countdown.run_if(in_state(GameState::Countdown)))
+        .add_systems(
+            Update,
+            (pause, step_game, apply_events)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(OnEnter(GameState::Paused), show_pause_menu)
+        .add_systems(
+            Update,
+            resume
+                .before(navigate_menus)
+                .run_if(in_state(GameState::Paused)),
+        )
+        .add_systems(OnEnter(GameState::GameOver), show_game_over)
+        .add_systems(
+            Update,
+            finish_game_over.run_if(in_state(GameState::GameOver)),
+        )
+        .add_systems(OnEnter(GameState::Results), show_results)
+        .add_systems(
+            Update,
+            restart.run_if(
+                in_state(GameState::Playing)
+                    .or(in_state(GameState::Paused))
+                    .or(in_state(GameState::GameOver))
+                    .or(in_state(GameState::Results)),
+            ),
+        )
+        .add_systems(
+            Update,
+            (navigate_menus, choose_menu_item, draw_menus).chain(),
+        )
+        .add_systems(Update, recolor_cells.run_if(resource_changed::<Settings>))
+        .add_systems(Update, (draw_next_queue, draw_hold))
+        .add_systems(
+            PostUpdate,
+            (
+                sync_active_piece,
+                update_ghost.after(sync_active_piece),
+                (snapshot_board, draw_board.run_if(resource_changed::<Board>)).chain(),
+            ),
+        )
+        .add_systems(Update, (log_tspins, show_callouts, expire_callouts))
+        .add_systems(Update, (update_hud, update_asset_count))
         .run();
 }
 
-fn setup(mut commands: Commands) {
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    settings: Res<Settings>,
+) {
     commands.spawn((
         Camera2d,
         Transform::from_xyz(
-            TILE_SIZE * COLS as f32 / 2.0,
+            (TILE_SIZE * COLS as f32 + SIDE_PANEL_WIDTH) / 2.0,
             -TILE_SIZE * ROWS as f32 / 2.0,
             0.0,
         ),
     ));
 
-    commands.insert_resource(PieceFactory {});
+    commands.spawn((
+        Sprite::from_color(
+            Color::srgb(0.1, 0.1, 0.1),
+            Vec2::new(SIDE_PANEL_WIDTH, TILE_SIZE * ROWS as f32),
+        ),
+        Transform::from_xyz(
+            TILE_SIZE * COLS as f32 + SIDE_PANEL_WIDTH / 2.0,
+            -TILE_SIZE * ROWS as f32 / 2.0,
+            -1.0,
+        ),
+    ));
+
+    spawn_panel_label(&mut commands, "HOLD", HOLD_TOP);
+
+    if settings.preview > 0 {
+        spawn_panel_label(&mut commands, "NEXT", NEXT_TOP);
+    }
+
+    commands.spawn(hud());
+
+    if cfg!(debug_assertions) {
+        commands.spawn((
+            Text2d::default(),
+            TextFont {
+                font_size: TILE_SIZE * 0.3,
+                ..default()
+            },
+            Transform::from_xyz(
+                TILE_SIZE * COLS as f32 + SIDE_PANEL_WIDTH / 2.0,
+                -TILE_SIZE * (ROWS as f32 - 0.5),
+                0.0,
+            ),
+            AssetCount,
+        ));
+    }
 
-    commands.insert_resource(Tick {
-        timer: Timer::from_seconds(1.0, TimerMode::Repeating),
-        mult: 1.0,
-    });
+    commands.insert_resource(MeshCache::new(&mut meshes));
 
-    commands.insert_resource(CurrentPieceHolder(None));
-    commands.insert_resource(Matrix::try_new(COLS as usize, ROWS as usize).unwrap());
+    let cell_materials = CellMaterials {
+        cells: Palette::cells()
+            .map(|cell| (cell, materials.add(settings.palette.color(cell))))
+            .collect(),
+        hold_used: materials.add(Color::srgb(0.3, 0.3, 0.3)),
+    };
+
+    let block_mesh = meshes.add(Rectangle::new(TILE_SIZE, TILE_SIZE));
+
+    commands.spawn((
+        Mesh2d(block_mesh.clone()),
+        MeshMaterial2d(materials.add(Color::srgba(1.0, 1.0, 1.0, 0.2))),
+        Transform::default(),
+        Visibility::Hidden,
+        GhostPiece,
+    ));
+
+    for row in VANISH_ROWS as usize..(VANISH_ROWS + ROWS) as usize {
+        for col in 0..COLS as usize {
+            commands.spawn((
+                Mesh2d(block_mesh.clone()),
+                MeshMaterial2d(cell_materials.get(Cell::Garbage).clone()),
+                Transform::from_translation(block_translation(row, col)),
+                Visibility::Hidden,
+                BoardCell { row, col },
+            ));
+        }
+    }
+
+    commands.insert_resource(cell_materials);
+
+    let session = new_session(&settings);
+    commands.insert_resource(Board(session.matrix().clone()));
+    commands.insert_resource(session);
 }
 
-fn handle_input(
-    input: Res<ButtonInput<KeyCode>>,
-    mut holder: ResMut<CurrentPieceHolder>,
-    mut query: Query<(&mut Mesh2d, &mut Transform), With<CurrentPieceTag>>,
-    mut tick: ResMut<Tick>,
+/// Starts a fresh game with the current settings.
+fn new_session(settings: &Settings) -> Session {
+    let seed = settings.seed.unwrap_or_else(|| rand::rng().random());
+    info!("Seed: {seed}");
+
+    Session::new(settings.game.clone(), seed)
+}
+
+/// Feeds this frame's actions to the game, or the next recorded step when
+/// watching a replay, and forwards what happened as messages.
+fn step_game(
+    input: Res<ButtonInput<Action>>,
+    time: Res<Time>,
+    mut session: ResMut<Session>,
+    mut events: MessageWriter<GameEvent>,
+) {
+    let session = &mut *session;
+
+    let (inputs, delta) = match &mut session.playback {
+        Some(step) => {
+            let Some(&recorded) = session.replay.steps().get(*step) else {
+                return;
+            };
+            *step += 1;
+            recorded
+        }
+        None => {
+            let mut inputs = Inputs::default();
+            for action in input.get_pressed() {
+                inputs.press(*action, input.just_pressed(*action));
+            }
+
+            session.replay.record(inputs, time.delta());
+            (inputs, time.delta())
+        }
+    };
+
+    for event in session.game.step(&inputs, delta) {
+        events.write(GameEvent(event));
+    }
+}
+
+/// Keeps the entities on screen in line with the game's events.
+fn apply_events(
+    mut commands: Commands,
+    mut events: MessageReader<GameEvent>,
+    session: Res<Session>,
+    active: Query<Entity, With<CurrentPieceTag>>,
+    mesh_cache: Res<MeshCache>,
+    cell_materials: Res<CellMaterials>,
+    mut next_state: ResMut<NextState<GameState>>,
 ) {
-    if input.just_pressed(KeyCode::KeyP) {
-        if tick.mult == 0.0 {
-            tick.mult = 1.0;
-        } else {
-            tick.mult = 0.0;
+    for GameEvent(event) in events.read() {
+        match event {
+            Event::Spawned(_) => {
+                for entity in &active {
+                    commands.entity(entity).despawn();
+                }
+
+                let Some(piece) = session.active() else {
+                    continue;
+                };
+
+                commands.spawn((
+                    Mesh2d(mesh_cache.get(piece.kind(), piece.rotation()).clone()),
+                    MeshMaterial2d(cell_materials.get(Cell::Piece(piece.kind())).clone()),
+                    Transform::from_translation(piece_translation(&piece.position())),
+                    CurrentPieceTag,
+                ));
+            }
+            Event::GameOver | Event::Finished => next_state.set(GameState::GameOver),
+            Event::Held(_) | Event::Locked { .. } | Event::LevelUp(_) => {}
         }
     }
+}
+
+/// Copies the matrix out of the game, marking `Board` as changed only when
+/// a cell actually differs.
+fn snapshot_board(session: Res<Session>, mut board: ResMut<Board>) {
+    if board.0 != *session.matrix() {
+        board.0 = session.matrix().clone();
+    }
+}
+
+fn draw_board(
+    board: Res<Board>,
+    cell_materials: Res<CellMaterials>,
+    mut cells: Query<(
+        &BoardCell,
+        &mut Visibility,
+        &mut MeshMaterial2d<ColorMaterial>,
+    )>,
+) {
+    for (board_cell, mut visibility, mut material) in &mut cells {
+        let cell = board.0.get(board_cell.row, board_cell.col);
 
-    let Some(current_piece) = holder.0.as_mut() else {
+        if cell == Cell::Empty {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+
+        visibility.set_if_neq(Visibility::Inherited);
+
+        let wanted = cell_materials.get(cell);
+        if material.0 != *wanted {
+            material.0 = wanted.clone();
+        }
+    }
+}
+
+fn draw_next_queue(
+    mut commands: Commands,
+    session: Res<Session>,
+    previews: Query<Entity, With<NextPreview>>,
+    cell_materials: Res<CellMaterials>,
+    mesh_cache: Res<MeshCache>,
+    mut drawn: Local<Vec<PieceKind>>,
+) {
+    let queue: Vec<_> = session.queue().collect();
+    if queue == *drawn {
         return;
-    };
+    }
+
+    for entity in &previews {
+        commands.entity(entity).despawn();
+    }
+
+    for (slot, kind) in queue.iter().enumerate() {
+        commands.spawn((
+            Mesh2d(mesh_cache.get(*kind, 0).clone()),
+            MeshMaterial2d(cell_materials.get(Cell::Piece(*kind)).clone()),
+            preview_transform(NEXT_TOP - TILE_SIZE - slot as f32 * PREVIEW_SLOT_HEIGHT),
+            NextPreview,
+        ));
+    }
 
-    let Ok((mut mesh_comp, mut transform)) = query.single_mut() else {
+    *drawn = queue;
+}
+
+fn draw_hold(
+    mut commands: Commands,
+    session: Res<Session>,
+    previews: Query<Entity, With<HoldPreview>>,
+    cell_materials: Res<CellMaterials>,
+    mesh_cache: Res<MeshCache>,
+    mut drawn: Local<(Option<PieceKind>, bool)>,
+) {
+    let hold = (session.hold(), session.hold_used());
+    if hold == *drawn {
+        return;
+    }
+    *drawn = hold;
+
+    for entity in &previews {
+        commands.entity(entity).despawn();
+    }
+
+    let (Some(kind), used) = hold else {
         return;
     };
 
-    if !(input.pressed(KeyCode::KeyZ) && input.pressed(KeyCode::KeyX)) {
-        if input.just_pressed(KeyCode::KeyZ) {
-            current_piece.rotate_ccw();
-        }
+    let material = if used {
+        &cell_materials.hold_used
+    } else {
+        cell_materials.get(Cell::Piece(kind))
+    };
+
+    commands.spawn((
+        Mesh2d(mesh_cache.get(kind, 0).clone()),
+        MeshMaterial2d(material.clone()),
+        preview_transform(HOLD_TOP - TILE_SIZE),
+        HoldPreview,
+    ));
+}
 
-        if input.just_pressed(KeyCode::KeyX) {
-            current_piece.rotate_cw();
+const HOLD_TOP: f32 = 0.0;
+const NEXT_TOP: f32 = HOLD_TOP - TILE_SIZE - PREVIEW_SLOT_HEIGHT;
+const PREVIEW_SLOT_HEIGHT: f32 = 3.0 * TILE_SIZE * PREVIEW_SCALE;
+/// Between the longest next queue and the HUD.
+const CALLOUT_Y: f32 = -TILE_SIZE * (ROWS as f32 - 4.5);
+
+fn preview_transform(top: f32) -> Transform {
+    Transform::from_xyz(TILE_SIZE * COLS as f32 + TILE_SIZE * 0.5, top, 0.0)
+        .with_scale(Vec3::splat(PREVIEW_SCALE))
+}
+
+fn spawn_panel_label(commands: &mut Commands, text: &str, top: f32) {
+    commands.spawn((
+        Text2d::new(text),
+        TextFont {
+            font_size: TILE_SIZE * 0.6,
+            ..default()
+        },
+        Transform::from_xyz(
+            TILE_SIZE * COLS as f32 + SIDE_PANEL_WIDTH / 2.0,
+            top - TILE_SIZE * 0.5,
+            0.0,
+        ),
+    ));
+}
+
+fn update_hud(
+    session: Res<Session>,
+    settings: Res<Settings>,
+    mut query: Query<&mut Text, With<Hud>>,
+) {
+    let text = hud_text(session.stats(), session.mode(), &settings.hud);
+
+    for mut hud in &mut query {
+        if hud.0 != text {
+            hud.0 = text.clone();
         }
+    }
+}
 
-        if input.just_pressed(KeyCode::KeyZ) || input.just_pressed(KeyCode::KeyX) {
-            *mesh_comp = Mesh2d(current_piece.get_mesh().to_owned());
+/// Keeps the mesh count on screen, which should stay flat however long the game runs.
+fn update_asset_count(meshes: Res<Assets<Mesh>>, mut query: Query<&mut Text2d, With<AssetCount>>) {
+    for mut text in &mut query {
+        let count = format!("Meshes: {}", meshes.len());
+
+        if text.0 != count {
+            text.0 = count;
         }
     }
+}
 
-    if !(input.pressed(KeyCode::KeyH) && input.pressed(KeyCode::KeyL)) {
-        if input.just_pressed(KeyCode::KeyH) {
-            move_piece(&mut transform, Direction::Left);
-        } else if input.just_pressed(KeyCode::KeyL) {
-            move_piece(&mut transform, Direction::Right);
+fn log_tspins(mut events: MessageReader<GameEvent>) {
+    for GameEvent(event) in events.read() {
+        if let Event::Locked { tspin, cleared, .. } = event
+            && *tspin != TSpin::None
+        {
+            info!("T-spin {:?} clearing {} lines", tspin, cleared.len());
         }
     }
 }
 
-fn advance_timer(time: Res<Time>, mut tick: ResMut<Tick>) {
-    let scaled_delta = time.delta().mul_f64(tick.mult);
+fn show_callouts(
+    mut commands: Commands,
+    mut events: MessageReader<GameEvent>,
+    callouts: Query<Entity, With<Callout>>,
+) {
+    let Some(text) = events
+        .read()
+        .filter_map(|GameEvent(event)| callout_text(event))
+        .last()
+    else {
+        return;
+    };
+
+    for entity in &callouts {
+        commands.entity(entity).despawn();
+    }
 
-    tick.timer.tick(scaled_delta);
+    commands.spawn((
+        Text2d::new(text),
+        TextFont {
+            font_size: TILE_SIZE * 0.4,
+            ..default()
+        },
+        TextLayout::new_with_justify(Justify::Center),
+        Transform::from_xyz(
+            TILE_SIZE * COLS as f32 + SIDE_PANEL_WIDTH / 2.0,
+            CALLOUT_Y,
+            0.0,
+        ),
+        Callout(Timer::from_seconds(1.5, TimerMode::Once)),
+    ));
+}
 
-    // tick.mult += FALL_SPEED_UP;
-    // TODO hack za laki pause
-    if tick.mult > 0.1 {
-        tick.mult += FALL_SPEED_UP;
+/// What to announce for a lock, if anything.
+fn callout_text(event: &Event) -> Option<String> {
+    let Event::Locked {
+        cleared,
+        tspin,
+        score,
+        ..
+    } = event
+    else {
+        return None;
+    };
+
+    let mut lines = Vec::new();
+
+    if score.back_to_back {
+        lines.push("BACK-TO-BACK".to_string());
+    }
+
+    let count = ["", " SINGLE", " DOUBLE", " TRIPLE"];
+    match tspin {
+        TSpin::Full => lines.push(format!("T-SPIN{}", count[cleared.len().min(3)])),
+        TSpin::Mini => lines.push(format!("T-SPIN MINI{}", count[cleared.len().min(3)])),
+        TSpin::None if cleared.len() >= 4 => lines.push("TETRIS".to_string()),
+        TSpin::None => {}
+    }
+
+    if score.combo > 0 {
+        lines.push(format!("{} COMBO", score.combo));
+    }
+
+    if score.perfect_clear {
+        lines.push("PERFECT CLEAR".to_string());
+    }
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
     }
 }
 
-fn apply_gravity(
+fn expire_callouts(
     mut commands: Commands,
-    tick: ResMut<Tick>,
-    mut query: Query<(Entity, &mut Transform), With<CurrentPieceTag>>,
-    mut matrix: ResMut<Matrix>,
-    mut piece_holder: ResMut<CurrentPieceHolder>,
+    time: Res<Time>,
+    mut callouts: Query<(Entity, &mut Callout)>,
+) {
+    for (entity, mut callout) in &mut callouts {
+        if callout.0.tick(time.delta()).is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn show_main_menu(mut commands: Commands) {
+    commands.spawn((
+        overlay(menu_text(Menu::new(
+            "TETRIS",
+            vec![
+                MenuItem::Start,
+                MenuItem::Mode,
+                MenuItem::Level,
+                MenuItem::Settings,
+                MenuItem::HighScores,
+                MenuItem::Replays,
+                MenuItem::Exit,
+            ],
+        ))),
+        DespawnOnExit(GameState::MainMenu),
+    ));
+}
+
+/// Seconds counted down before a game starts.
+const COUNTDOWN_SECONDS: f32 = 3.0;
+
+fn show_countdown(mut commands: Commands) {
+    commands.insert_resource(StateTimer(Timer::from_seconds(
+        COUNTDOWN_SECONDS,
+        TimerMode::Once,
+    )));
+
+    commands.spawn((
+        overlay((
+            Text::default(),
+            TextFont {
+                font_size: 96.0,
+                ..default()
+            },
+            CountdownText,
+        )),
+        DespawnOnExit(GameState::Countdown),
+    ));
+}
+
+fn run_countdown(
+    time: Res<Time>,
+    mut timer: ResMut<StateTimer>,
+    mut text: Query<&mut Text, With<CountdownText>>,
+    mut next_state: ResMut<NextState<GameState>>,
 ) {
-    if tick.timer.just_finished() {
-        if let Ok((entity, mut transform)) = query.single_mut() {
-            move_piece(&mut transform, Direction::Down);
+    if timer.0.tick(time.delta()).is_finished() {
+        next_state.set(GameState::Playing);
+        return;
+    }
 
-            let piece = piece_holder.0.as_ref().unwrap();
+    let seconds = timer.0.remaining_secs().ceil().to_string();
+    for mut text in &mut text {
+        if text.0 != seconds {
+            text.0 = seconds.clone();
+        }
+    }
+}
 
-            let piece_table = piece.get_table();
-            let piece_indicies = get_piece_indicies(&transform);
+fn pause(input: Res<ButtonInput<Action>>, mut next_state: ResMut<NextState<GameState>>) {
+    if input.just_pressed(Action::Pause) {
+        next_state.set(GameState::Paused);
+    }
+}
 
-            let collided = check_for_colision(&matrix, piece_table, &piece_indicies);
+fn show_pause_menu(mut commands: Commands) {
+    commands.spawn((
+        overlay(menu_text(Menu::new(
+            "PAUSED",
+            vec![
+                MenuItem::Resume,
+                MenuItem::Restart,
+                MenuItem::Settings,
+                MenuItem::Quit,
+            ],
+        ))),
+        DespawnOnExit(GameState::Paused),
+    ));
+}
 
-            if collided {
-                move_piece(&mut transform, Direction::Up);
+/// The pause button resumes too, unless it would skip out of a submenu.
+fn resume(
+    input: Res<ButtonInput<Action>>,
+    menus: Query<&Menu>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if input.just_pressed(Action::Pause) && menus.iter().all(Menu::is_top_level) {
+        next_state.set(GameState::Playing);
+    }
+}
 
-                let recalculated_indicies = get_piece_indicies(&transform);
-                dbg!(&piece_indicies, &recalculated_indicies);
-                fix_piece(&mut matrix, piece_table, &recalculated_indicies);
+/// How long the topped out stack stays on screen before the results.
+const GAME_OVER_SECONDS: f32 = 2.0;
 
-                print!("{}", matrix.as_ref());
+fn show_game_over(mut commands: Commands, session: Res<Session>) {
+    commands.insert_resource(StateTimer(Timer::from_seconds(
+        GAME_OVER_SECONDS,
+        TimerMode::Once,
+    )));
 
-                piece_holder.0 = None;
-                commands.entity(entity).remove::<CurrentPieceTag>();
-            }
+    commands.spawn((
+        overlay((
+            Text::new(match session.ending() {
+                Some(Ending::GoalReached) => "FINISHED",
+                _ => "GAME OVER",
+            }),
+            TextFont {
+                font_size: 64.0,
+                ..default()
+            },
+        )),
+        DespawnOnExit(GameState::GameOver),
+    ));
+}
+
+fn finish_game_over(
+    time: Res<Time>,
+    mut timer: ResMut<StateTimer>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if timer.0.tick(time.delta()).is_finished() {
+        next_state.set(GameState::Results);
+    }
+}
+
+/// Shows how the game went, recording it in the high scores and replays
+/// first unless it was a replay.
+fn show_results(
+    mut commands: Commands,
+    session: Res<Session>,
+    mut high_scores: ResMut<HighScores>,
+    mut replays: ResMut<Replays>,
+) {
+    let stats = session.stats();
+    let mut title = format!(
+        "RESULTS: {}\n\nScore: {}\nLevel: {}\nLines: {}\nPieces: {}\nTime: {}\n",
+        session.mode().name().to_uppercase(),
+        stats.score,
+        stats.level,
+        stats.lines,
+        stats.pieces,
+        format_time(stats.time)
+    );
+
+    let items = if session.playback.is_some() {
+        vec![MenuItem::Quit]
+    } else {
+        let ending = session.ending().unwrap_or(Ending::ToppedOut);
+        if let Some(place) = high_scores.record(session.mode(), Entry::new(stats), ending) {
+            high_scores.save();
+            title.push_str(&format!("\nNew high score, #{}!\n", place + 1));
         }
+
+        replays.record(session.replay.clone(), Entry::new(stats));
+        vec![MenuItem::Restart, MenuItem::Quit]
     };
+
+    commands.spawn((
+        overlay(menu_text(Menu::new(title, items))),
+        DespawnOnExit(GameState::Results),
+    ));
 }
 
-#[allow(dead_code)]
-fn bounds(
+fn restart(
     mut commands: Commands,
-    mut holder: ResMut<CurrentPieceHolder>,
-    mut query: Query<(Entity, &mut Transform), With<CurrentPieceTag>>,
+    input: Res<ButtonInput<Action>>,
+    pieces: Query<Entity, With<CurrentPieceTag>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    settings: Res<Settings>,
+) {
+    if input.just_pressed(Action::Restart) {
+        new_game(&mut commands, &settings, &pieces);
+        next_state.set(GameState::Countdown);
+    }
+}
+
+/// Replaces the session with a fresh game, removing the old active piece.
+fn new_game(
+    commands: &mut Commands,
+    settings: &Settings,
+    pieces: &Query<Entity, With<CurrentPieceTag>>,
+) {
+    replace_session(commands, new_session(settings), pieces);
+}
+
+fn replace_session(
+    commands: &mut Commands,
+    session: Session,
+    pieces: &Query<Entity, With<CurrentPieceTag>>,
 ) {
-    if let Some(_) = holder.0.as_ref() {
-        if let Ok((entity, transform)) = query.single_mut() {
-            if transform.translation.y < -(TILE_SIZE * (ROWS - 3) as f32) {
-                holder.0 = None;
-                commands.entity(entity).remove::<CurrentPieceTag>();
+    for entity in pieces {
+        commands.entity(entity).despawn();
+    }
+
+    commands.insert_resource(session);
+}
+
+fn choose_menu_item(
+    mut commands: Commands,
+    mut chosen: MessageReader<MenuChosen>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut settings: ResMut<Settings>,
+    high_scores: Res<HighScores>,
+    replays: Res<Replays>,
+    pieces: Query<Entity, With<CurrentPieceTag>>,
+    mut menus: Query<&mut Menu>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    for &MenuChosen { item, step } in chosen.read() {
+        match item {
+            MenuItem::Start => {
+                new_game(&mut commands, &settings, &pieces);
+                next_state.set(GameState::Countdown);
+            }
+            MenuItem::Mode => settings.game.mode = cycle(&Mode::ALL, settings.mode, step),
+            MenuItem::Level => {
+                let level = settings.start_level.min(MAX_START_LEVEL) as i32 - 1 + step;
+                settings.game.start_level = level.rem_euclid(MAX_START_LEVEL as i32) as u32 + 1;
+            }
+            MenuItem::Resume => next_state.set(GameState::Playing),
+            MenuItem::Restart => {
+                new_game(&mut commands, &settings, &pieces);
+                next_state.set(GameState::Countdown);
+            }
+            MenuItem::Quit => {
+                new_game(&mut commands, &settings, &pieces);
+                next_state.set(GameState::MainMenu);
+            }
+            MenuItem::Exit => {
+                exit.write(AppExit::Success);
             }
+            MenuItem::HighScores => {
+                let text = high_score_text(settings.mode, &high_scores);
+                for mut menu in &mut menus {
+                    menu.open(text.clone(), vec![MenuItem::Back]);
+                }
+            }
+            MenuItem::Replays => {
+                let text = replay_text(&replays);
+                let items = (0..replays.get().len())
+                    .map(MenuItem::Watch)
+                    .chain([MenuItem::Back])
+                    .collect::<Vec<_>>();
+                for mut menu in &mut menus {
+                    menu.open(text.clone(), items.clone());
+                }
+            }
+            MenuItem::Watch(index) => {
+                let (replay, _) = &replays.get()[index];
+                replace_session(&mut commands, Session::watch(replay.clone()), &pieces);
+                next_state.set(GameState::Countdown);
+            }
+            MenuItem::Ghost => settings.ghost = !settings.ghost,
+            MenuItem::Hud(stat) => {
+                if !settings.hud.remove(&stat) {
+                    settings.hud.insert(stat);
+                }
+            }
+            MenuItem::Palette => settings.palette = cycle(&Palette::ALL, settings.palette, step),
+            MenuItem::Back if *state.get() == GameState::Paused => {
+                next_state.set(GameState::Playing);
+            }
+            MenuItem::Back | MenuItem::Settings => {}
         }
     }
 }
 
-fn create_piece(
-    mut commands: Commands,
-    mut holder: ResMut<CurrentPieceHolder>,
-    mut factory: ResMut<PieceFactory>,
-    meshes: ResMut<Assets<Mesh>>,
+fn high_score_text(mode: Mode, high_scores: &HighScores) -> String {
+    let mut text = format!("HIGH SCORES: {}\n", mode.name().to_uppercase());
+
+    if high_scores.get(mode).is_empty() {
+        text.push_str("\nNo results yet\n");
+    }
+
+    for (place, entry) in high_scores.get(mode).iter().enumerate() {
+        text.push_str(&format!(
+            "\n{}. {}  {} lines  {}",
+            place + 1,
+            entry.score,
+            entry.lines,
+            format_time(entry.time)
+        ));
+    }
+
+    text
+}
+
+fn replay_text(replays: &Replays) -> String {
+    let mut text = "REPLAYS\n".to_string();
+
+    if replays.get().is_empty() {
+        text.push_str("\nNo games played yet\n");
+    }
+
+    for (index, (replay, entry)) in replays.get().iter().enumerate() {
+        text.push_str(&format!(
+            "\n{}. {}  {}  {} lines  {}",
+            index + 1,
+            replay.config.mode.name(),
+            entry.score,
+            entry.lines,
+            format_time(entry.time)
+        ));
+    }
+
+    text
+}
+
+/// Repaints the existing materials when the palette changes.
+fn recolor_cells(
+    settings: Res<Settings>,
+    cell_materials: Res<CellMaterials>,
     mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
-    if let Some(_) = holder.0 {
-        return;
+    for (cell, handle) in &cell_materials.cells {
+        if let Some(material) = materials.get_mut(handle) {
+            material.color = settings.palette.color(*cell);
+        }
     }
+}
 
-    let piece = factory.create_piece(meshes);
-    let mesh_handle = piece.get_mesh().to_owned();
+#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
+enum GameState {
+    #[default]
+    MainMenu,
+    Countdown,
+    Playing,
+    Paused,
+    /// The game has ended, the final stack is shown for a moment.
+    GameOver,
+    Results,
+}
 
-    let material = materials.add(get_random_color());
+/// The game being played. Everything on screen is drawn from it.
+#[derive(Resource, Deref, DerefMut)]
+struct Session {
+    #[deref]
+    game: Game,
+    /// Every step so far, or the one being watched.
+    replay: Replay,
+    /// How many steps of `replay` were played back, `None` unless watching it.
+    playback: Option<usize>,
+}
 
-    commands.spawn((
-        Mesh2d(mesh_handle),
-        MeshMaterial2d(material.clone()),
-        Transform::from_xyz(0.0, 0.0, 0.0),
-        CurrentPieceTag,
-    ));
+impl Session {
+    fn new(config: GameConfig, seed: u64) -> Self {
+        Session {
+            game: Game::new(config.clone(), seed),
+            replay: Replay::new(config, seed),
+            playback: None,
+        }
+    }
 
-    holder.0 = Some(piece);
+    fn watch(replay: Replay) -> Self {
+        Session {
+            game: replay.game(),
+            replay,
+            playback: Some(0),
+        }
+    }
 }
 
-// fn check_for_collision(
-//     tick: ResMut<Tick>,
-//     mut query: Query<&mut Transform, With<CurrentPieceTag>>,
-// ) {
-// }
+/// An `Event` from the last step of the `Session`.
+#[derive(Message)]
+struct GameEvent(Event);
 
 #[derive(Component)]
 struct CurrentPieceTag;
 
+#[derive(Component)]
+struct CountdownText;
+
+/// Times the countdown and the game over screen.
+#[derive(Resource)]
+struct StateTimer(Timer);
+
+/// Debug readout of the number of mesh assets, only spawned in debug builds.
+#[derive(Component)]
+struct AssetCount;
+
+/// Text announcing a special clear, removed when its timer runs out.
+#[derive(Component)]
+struct Callout(Timer);
+
+/// Copy of the game's `Matrix`, only replaced when the stack changes so that
+/// drawing it can rely on change detection.
 #[derive(Resource)]
-struct CurrentPieceHolder(Option<BoxedPiece>);
+struct Board(Matrix);
+
+/// One cell of the settled stack, shown while the `Matrix` cell is filled.
+#[derive(Component)]
+struct BoardCell {
+    row: usize,
+    col: usize,
+}
+
+/// Translucent outline of where the active piece would land.
+#[derive(Component)]
+struct GhostPiece;
+
+#[derive(Component)]
+struct NextPreview;
+
+#[derive(Component)]
+struct HoldPreview;
 
+/// Material of every filled cell in the chosen palette.
 #[derive(Resource)]
-struct PieceFactory();
-
-impl PieceFactory {
-    fn create_piece(&mut self, mut meshes: ResMut<Assets<Mesh>>) -> BoxedPiece {
-        let mut rng = rand::rng();
-
-        match rng.random_range(0..=5) {
-            0 => Box::new(OShape::new(&mut meshes)),
-            1 => Box::new(IShape::new(&mut meshes)),
-            2 => Box::new(LShape::new(&mut meshes)),
-            3 => Box::new(TShape::new(&mut meshes)),
-            4 => Box::new(SShape::new(&mut meshes)),
-            5 => Box::new(ZShape::new(&mut meshes)),
-            _ => unreachable!(),
-        }
+struct CellMaterials {
+    cells: HashMap<Cell, Handle<ColorMaterial>>,
+    /// Used for the held piece while hold can't be used again.
+    hold_used: Handle<ColorMaterial>,
+}
+
+impl CellMaterials {
+    fn get(&self, cell: Cell) -> &Handle<ColorMaterial> {
+        &self.cells[&cell]
     }
 }
 
-fn get_random_color() -> Color {
-    let mut rng = rand::rng();
+fn update_ghost(
+    session: Res<Session>,
+    settings: Res<Settings>,
+    active: Query<&Mesh2d, (With<CurrentPieceTag>, Without<GhostPiece>)>,
+    mut ghost: Query<(&mut Mesh2d, &mut Transform, &mut Visibility), With<GhostPiece>>,
+) {
+    let Ok((mut ghost_mesh, mut ghost_transform, mut visibility)) = ghost.single_mut() else {
+        return;
+    };
+
+    let (true, Some(landing), Ok(mesh)) = (settings.ghost, session.ghost(), active.single()) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
 
-    Color::linear_rgb(
-        rng.random_range(0.0..=1.0),
-        rng.random_range(0.0..=1.0),
-        rng.random_range(0.0..=1.0),
-    )
+    *ghost_mesh = mesh.clone();
+    // Drawn just below the active piece so it never covers it.
+    ghost_transform.translation = piece_translation(&landing).with_z(-0.5);
+    *visibility = Visibility::Visible;
 }
 
-#[derive(Resource)]
-struct Tick {
-    timer: Timer,
-    mult: f64,
-}
-
-enum Direction {
-    Left,
-    Right,
-    Up,
-    Down,
-}
-
-// use rand::prelude::*;
-//
-// fn update_random_field(mut matrix: ResMut<Matrix>) {
-//     let mut rng = rand::rng();
-//
-//     let col = rng.random_range(0..matrix.height());
-//     let row = rng.random_range(0..matrix.width());
-//
-//     // println!("col: {}, row: {}", col, row);
-//     matrix.set(col, row);
-//     matrix.clear(0, 0);
-// }
-
-// Dodaj ovde "vece" parametre
-// Tako da mozes da dodas neku funkciju on_piece_move
-// i onda da imas sve sto ti treba da joj prosledis
-fn move_piece(transform: &mut Transform, direction: Direction) {
-    match direction {
-        Direction::Left => {
-            transform.translation.x -= TILE_SIZE;
-        }
-        Direction::Right => {
-            transform.translation.x += TILE_SIZE;
-        }
-        Direction::Down => {
-            transform.translation.y -= TILE_SIZE;
-        }
-        Direction::Up => {
-            transform.translation.y += TILE_SIZE;
-        }
+/// Moves and turns the active piece entity to match the game.
+fn sync_active_piece(
+    session: Res<Session>,
+    mesh_cache: Res<MeshCache>,
+    mut query: Query<(&mut Mesh2d, &mut Transform), With<CurrentPieceTag>>,
+) {
+    let (Some(piece), Ok((mut mesh, mut transform))) = (session.active(), query.single_mut())
+    else {
+        return;
+    };
+
+    let translation = piece_translation(&piece.position());
+    if transform.translation != translation {
+        transform.translation = translation;
     }
 
-    // dbg!(get_piece_indicies(&transform));
+    let rotated = mesh_cache.get(piece.kind(), piece.rotation());
+    if mesh.0 != *rotated {
+        mesh.0 = rotated.clone();
+    }
 }
 
 fn get_window_settings() -> WindowPlugin {
     WindowPlugin {
         primary_window: Some(Window {
-            resolution: WindowResolution::new(TILE_SIZE as u32 * COLS, TILE_SIZE as u32 * ROWS)
-                .with_scale_factor_override(1.0),
+            resolution: WindowResolution::new(
+                TILE_SIZE as u32 * COLS + SIDE_PANEL_WIDTH as u32,
+                TILE_SIZE as u32 * ROWS,
+            )
+            .with_scale_factor_override(1.0),
             ..default()
         }),
         ..default()
@@ -1,13 +1,33 @@
 use std::fmt::Display;
 
-use crate::pieces::Table;
-use bevy::ecs::resource::Resource;
+use crate::consts::VANISH_ROWS;
+use crate::pieces::{PieceKind, Table};
 
 use crate::pieces::PieceIndicies;
 
-#[derive(Resource)]
+/// What fills a cell of the `Matrix`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cell {
+    #[default]
+    Empty,
+    Piece(PieceKind),
+    /// Filled by something other than a locked piece, like incoming garbage.
+    Garbage,
+}
+
+impl Display for Cell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cell::Empty => write!(f, "0"),
+            Cell::Piece(kind) => write!(f, "{kind:?}"),
+            Cell::Garbage => write!(f, "G"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Matrix {
-    elements: Vec<Vec<u8>>,
+    elements: Vec<Vec<Cell>>,
 }
 
 const MIN_WIDTH: usize = 7;
@@ -24,7 +44,7 @@ impl Matrix {
         };
 
         Some(Matrix {
-            elements: vec![vec![0u8; width]; height],
+            elements: vec![vec![Cell::Empty; width]; height],
         })
     }
 
@@ -36,16 +56,53 @@ impl Matrix {
         self.elements.first().unwrap().len()
     }
 
-    fn get(&self, row: usize, col: usize) -> u8 {
+    pub fn get(&self, row: usize, col: usize) -> Cell {
         self.elements[row][col]
     }
 
-    fn set(&mut self, row: usize, col: usize) {
-        self.elements[row][col] = 1;
+    pub fn set(&mut self, row: usize, col: usize, cell: Cell) {
+        self.elements[row][col] = cell;
+    }
+
+    /// Whether `(row, col)` lies inside the matrix, vanish zone included.
+    pub fn contains(&self, row: i32, col: i32) -> bool {
+        row >= 0 && (row as usize) < self.height() && col >= 0 && (col as usize) < self.width()
+    }
+
+    /// Whether a cell is filled. Anything outside the matrix counts as filled.
+    pub fn is_occupied(&self, row: i32, col: i32) -> bool {
+        if !self.contains(row, col) {
+            return true;
+        }
+
+        self.get(row as usize, col as usize) != Cell::Empty
     }
 
-    fn clear(&mut self, row: usize, col: usize) {
-        self.elements[row][col] = 0;
+    pub fn is_empty(&self) -> bool {
+        self.elements
+            .iter()
+            .flatten()
+            .all(|&cell| cell == Cell::Empty)
+    }
+
+    fn is_row_full(&self, row: usize) -> bool {
+        self.elements[row].iter().all(|&cell| cell != Cell::Empty)
+    }
+
+    /// Removes every full row and shifts the rows above it down.
+    /// Returns the indices of the removed rows, from top to bottom.
+    pub fn clear_full_rows(&mut self) -> Vec<usize> {
+        let cleared: Vec<usize> = (0..self.height())
+            .filter(|&row| self.is_row_full(row))
+            .collect();
+
+        let width = self.width();
+        for &row in &cleared {
+            self.elements.remove(row);
+            self.elements.insert(0, vec![Cell::Empty; width]);
+        }
+
+        cleared
     }
 }
 
@@ -55,61 +112,61 @@ impl Display for Matrix {
             for j in 0..self.width() {
                 write!(f, "{} ", self.get(i, j))?
             }
-            write!(f, "\n")?
+            writeln!(f)?
         }
         Ok(())
     }
 }
 
 pub fn check_for_colision(matrix: &Matrix, table: &Table, piece_indicies: &PieceIndicies) -> bool {
-    let width = matrix.width();
-    let height = matrix.height();
-
-    for i in (0..=3).rev() {
-        for j in 0..=3 {
-            if table[i][j] == 0 {
+    for (i, table_row) in table.iter().enumerate().rev() {
+        for (j, &cell) in table_row.iter().enumerate() {
+            if cell == 0 {
                 continue;
             }
 
-            let row = piece_indicies.i + i as i32;
-            let col = piece_indicies.j + j as i32;
-
-            if row < 0 || row as usize >= height {
-                return true;
-            }
-
-            if col < 0 || col as usize >= width {
-                return true;
-            }
-
-            let row = row as usize;
-            let col = col as usize;
-
-            if matrix.get(row, col) == 1 {
+            if matrix.is_occupied(piece_indicies.i + i as i32, piece_indicies.j + j as i32) {
                 return true;
             }
         }
     }
 
-    return false;
+    false
 }
 
-pub fn fix_piece(matrix: &mut Matrix, table: &Table, piece_indicies: &PieceIndicies) {
-    for i in 0..=3 {
-        for j in 0..=3 {
-            if table[i][j] == 1 {
-                matrix.set(piece_indicies.i as usize + i, piece_indicies.j as usize + j);
+pub fn fix_piece(
+    matrix: &mut Matrix,
+    table: &Table,
+    piece_indicies: &PieceIndicies,
+    kind: PieceKind,
+) {
+    for (i, table_row) in table.iter().enumerate() {
+        for (j, &cell) in table_row.iter().enumerate() {
+            let row = piece_indicies.i + i as i32;
+            let col = piece_indicies.j + j as i32;
+
+            // Cells sticking out of the matrix are lost rather than wrapping around.
+            if cell == 1 && matrix.contains(row, col) {
+                matrix.set(row as usize, col as usize, Cell::Piece(kind));
             }
         }
     }
 }
 
+/// Lock-out: every cell of the piece ended up in the vanish zone.
+pub fn is_lock_out(table: &Table, piece_indicies: &PieceIndicies) -> bool {
+    table.iter().enumerate().all(|(i, table_row)| {
+        table_row.iter().all(|&cell| cell == 0)
+            || piece_indicies.i + (i as i32) < VANISH_ROWS as i32
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
         consts::{COLS, ROWS},
-        matrix::{Matrix, check_for_colision},
-        pieces::PieceIndicies,
+        matrix::{Cell, Matrix, check_for_colision, fix_piece},
+        pieces::{PieceIndicies, PieceKind},
     };
 
     #[test]
@@ -120,7 +177,7 @@ mod tests {
 
         let collided = check_for_colision(&matrix, &table, &indicies);
 
-        assert!(collided == false);
+        assert!(!collided);
     }
 
     #[test]
@@ -129,11 +186,11 @@ mod tests {
         let table = [[1, 1, 0, 0], [1, 1, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0]];
         let indicies = PieceIndicies { i: 0, j: 1 };
 
-        matrix.set(0, 1);
+        matrix.set(0, 1, Cell::Garbage);
 
         let collided = check_for_colision(&matrix, &table, &indicies);
 
-        assert!(collided == true);
+        assert!(collided);
     }
 
     #[test]
@@ -142,21 +199,54 @@ mod tests {
         let table = [[1, 1, 0, 0], [1, 1, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0]];
         let indicies = PieceIndicies { i: 3, j: 3 };
 
-        matrix.set(2, 2);
-        matrix.set(3, 2);
-        matrix.set(4, 2);
-        matrix.set(5, 2);
-        matrix.set(5, 3);
-        matrix.set(5, 4);
-        matrix.set(5, 5);
-        matrix.set(4, 5);
-        matrix.set(3, 5);
-        matrix.set(2, 5);
-        matrix.set(2, 4);
-        matrix.set(2, 3);
+        matrix.set(2, 2, Cell::Garbage);
+        matrix.set(3, 2, Cell::Garbage);
+        matrix.set(4, 2, Cell::Garbage);
+        matrix.set(5, 2, Cell::Garbage);
+        matrix.set(5, 3, Cell::Garbage);
+        matrix.set(5, 4, Cell::Garbage);
+        matrix.set(5, 5, Cell::Garbage);
+        matrix.set(4, 5, Cell::Garbage);
+        matrix.set(3, 5, Cell::Garbage);
+        matrix.set(2, 5, Cell::Garbage);
+        matrix.set(2, 4, Cell::Garbage);
+        matrix.set(2, 3, Cell::Garbage);
 
         let collided = check_for_colision(&matrix, &table, &indicies);
 
-        assert!(collided == false);
+        assert!(!collided);
+    }
+
+    #[test]
+    fn test_fix_piece_above_the_matrix() {
+        let mut matrix = Matrix::try_new(COLS as usize, ROWS as usize).unwrap();
+        let table = [[1, 1, 0, 0], [1, 1, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0]];
+        let indicies = PieceIndicies { i: -1, j: 0 };
+
+        fix_piece(&mut matrix, &table, &indicies, PieceKind::O);
+
+        assert_eq!(matrix.get(0, 0), Cell::Piece(PieceKind::O));
+        assert_eq!(matrix.get(1, 0), Cell::Empty);
+    }
+
+    #[test]
+    fn test_clear_full_rows() {
+        let mut matrix = Matrix::try_new(COLS as usize, ROWS as usize).unwrap();
+        let bottom = ROWS as usize - 1;
+
+        for col in 0..COLS as usize {
+            matrix.set(bottom, col, Cell::Garbage);
+            matrix.set(bottom - 2, col, Cell::Garbage);
+        }
+        matrix.set(bottom - 1, 0, Cell::Garbage);
+        matrix.set(bottom - 3, 4, Cell::Garbage);
+
+        let cleared = matrix.clear_full_rows();
+
+        assert_eq!(cleared, vec![bottom - 2, bottom]);
+        assert_eq!(matrix.get(bottom, 0), Cell::Garbage);
+        assert_eq!(matrix.get(bottom - 1, 4), Cell::Garbage);
+        assert!(!matrix.is_row_full(bottom));
+        assert!((0..COLS as usize).all(|col| matrix.get(0, col) == Cell::Empty));
     }
 }
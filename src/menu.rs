@@ -0,0 +1,295 @@
+//! Menus drawn as Bevy UI overlays and moved through with `MenuAction`s.
+//!
+//! A `Menu` only tracks which item is selected. Choosing an item sends a
+//! `MenuChosen` message for the app to act on, except for opening the
+//! settings page and backing out of it, which the menu handles itself.
+//! Items with a value, like the game mode, also send one when moved left
+//! or right.
+
+use bevy::prelude::*;
+
+use crate::{hud::HudStat, input::MenuAction, settings::Settings};
+
+/// A line of a menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    Start,
+    Mode,
+    Level,
+    Resume,
+    Restart,
+    Settings,
+    /// Abandons the game and goes back to the title screen.
+    Quit,
+    /// Closes the app.
+    Exit,
+    HighScores,
+    Replays,
+    /// Plays back the replay at this index, newest first.
+    Watch(usize),
+    Ghost,
+    Palette,
+    Hud(HudStat),
+    Back,
+}
+
+impl MenuItem {
+    fn label(self, settings: &Settings) -> String {
+        match self {
+            MenuItem::Start => "Start".to_string(),
+            MenuItem::Mode => format!("Mode: < {} >", settings.mode.name()),
+            MenuItem::Level => format!("Level: < {} >", settings.start_level),
+            MenuItem::Resume => "Resume".to_string(),
+            MenuItem::Restart => "Restart".to_string(),
+            MenuItem::Settings => "Settings".to_string(),
+            MenuItem::Quit => "Quit to title".to_string(),
+            MenuItem::Exit => "Exit".to_string(),
+            MenuItem::HighScores => "High scores".to_string(),
+            MenuItem::Replays => "Replays".to_string(),
+            MenuItem::Watch(index) => format!("Watch #{}", index + 1),
+            MenuItem::Ghost => format!("Ghost piece: {}", on_off(settings.ghost)),
+            MenuItem::Palette => format!("Palette: < {} >", settings.palette.name()),
+            MenuItem::Hud(stat) => {
+                let shown = settings.hud.contains(&stat);
+                format!(
+                    "{}: {}",
+                    stat.name(),
+                    if shown { "shown" } else { "hidden" }
+                )
+            }
+            MenuItem::Back => "Back".to_string(),
+        }
+    }
+
+    /// Whether the item holds a value that left and right change.
+    fn has_value(self) -> bool {
+        matches!(
+            self,
+            MenuItem::Mode
+                | MenuItem::Level
+                | MenuItem::Ghost
+                | MenuItem::Palette
+                | MenuItem::Hud(_)
+        )
+    }
+}
+
+/// The entry `step` places after `current` in `all`, wrapping around.
+pub fn cycle<T: Copy + PartialEq>(all: &[T], current: T, step: i32) -> T {
+    let index = all.iter().position(|&item| item == current).unwrap_or(0) as i32;
+    all[(index + step).rem_euclid(all.len() as i32) as usize]
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value { "on" } else { "off" }
+}
+
+const SETTINGS_ITEMS: [MenuItem; 10] = [
+    MenuItem::Ghost,
+    MenuItem::Palette,
+    MenuItem::Hud(HudStat::Score),
+    MenuItem::Hud(HudStat::Level),
+    MenuItem::Hud(HudStat::Lines),
+    MenuItem::Hud(HudStat::Time),
+    MenuItem::Hud(HudStat::Pps),
+    MenuItem::Hud(HudStat::InputsPerPiece),
+    MenuItem::Hud(HudStat::Apm),
+    MenuItem::Back,
+];
+
+/// A titled list of items, one of them selected.
+#[derive(Component)]
+pub struct Menu {
+    title: String,
+    items: Vec<MenuItem>,
+    selected: usize,
+    /// Menus this one was opened from, each with its selection.
+    parents: Vec<(String, Vec<MenuItem>, usize)>,
+}
+
+impl Menu {
+    pub fn new(title: impl Into<String>, items: Vec<MenuItem>) -> Self {
+        Menu {
+            title: title.into(),
+            items,
+            selected: 0,
+            parents: Vec::new(),
+        }
+    }
+
+    pub fn selected(&self) -> MenuItem {
+        self.items[self.selected]
+    }
+
+    pub fn is_top_level(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// Moves the selection, wrapping around at either end.
+    fn move_by(&mut self, offset: isize) {
+        let len = self.items.len() as isize;
+        self.selected = (self.selected as isize + offset).rem_euclid(len) as usize;
+    }
+
+    /// Shows a submenu in place of this one until backed out of.
+    pub fn open(&mut self, title: impl Into<String>, items: Vec<MenuItem>) {
+        let title = std::mem::replace(&mut self.title, title.into());
+        let items = std::mem::replace(&mut self.items, items);
+        self.parents.push((title, items, self.selected));
+        self.selected = 0;
+    }
+
+    /// Returns to the menu this one was opened from, or `false` at the top level.
+    fn back(&mut self) -> bool {
+        let Some((title, items, selected)) = self.parents.pop() else {
+            return false;
+        };
+
+        self.title = title;
+        self.items = items;
+        self.selected = selected;
+        true
+    }
+
+    fn text(&self, settings: &Settings) -> String {
+        let mut text = format!("{}\n", self.title);
+
+        for (index, item) in self.items.iter().enumerate() {
+            let label = item.label(settings);
+            if index == self.selected {
+                text.push_str(&format!("\n> {label} <"));
+            } else {
+                text.push_str(&format!("\n{label}"));
+            }
+        }
+
+        text
+    }
+}
+
+/// Sent when a menu item is chosen or its value is changed. Backing out of
+/// a top level menu sends `MenuItem::Back`.
+#[derive(Message)]
+pub struct MenuChosen {
+    pub item: MenuItem,
+    /// -1 when the value was moved left, 1 when moved right or chosen.
+    pub step: i32,
+}
+
+impl MenuChosen {
+    fn new(item: MenuItem) -> Self {
+        MenuChosen { item, step: 1 }
+    }
+}
+
+/// A dimmed layer over the whole window with `content` in the middle.
+pub fn overlay(content: impl Bundle) -> impl Bundle {
+    (
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        children![content],
+    )
+}
+
+/// Centred text showing `menu`, for use inside an `overlay`.
+pub fn menu_text(menu: Menu) -> impl Bundle {
+    (
+        Text::default(),
+        TextFont {
+            font_size: 32.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(Justify::Center),
+        menu,
+    )
+}
+
+pub fn navigate_menus(
+    input: Res<ButtonInput<MenuAction>>,
+    mut menus: Query<&mut Menu>,
+    mut chosen: MessageWriter<MenuChosen>,
+) {
+    for mut menu in &mut menus {
+        if input.just_pressed(MenuAction::Up) {
+            menu.move_by(-1);
+        }
+
+        if input.just_pressed(MenuAction::Down) {
+            menu.move_by(1);
+        }
+
+        for (action, step) in [(MenuAction::Left, -1), (MenuAction::Right, 1)] {
+            if input.just_pressed(action) && menu.selected().has_value() {
+                chosen.write(MenuChosen {
+                    item: menu.selected(),
+                    step,
+                });
+            }
+        }
+
+        if input.just_pressed(MenuAction::Back) && !menu.back() {
+            chosen.write(MenuChosen::new(MenuItem::Back));
+        }
+
+        if !input.just_pressed(MenuAction::Confirm) {
+            continue;
+        }
+
+        match menu.selected() {
+            MenuItem::Settings => menu.open("SETTINGS", SETTINGS_ITEMS.to_vec()),
+            MenuItem::Back => {
+                if !menu.back() {
+                    chosen.write(MenuChosen::new(MenuItem::Back));
+                }
+            }
+            item => {
+                chosen.write(MenuChosen::new(item));
+            }
+        }
+    }
+}
+
+pub fn draw_menus(settings: Res<Settings>, mut menus: Query<(Ref<Menu>, &mut Text)>) {
+    for (menu, mut text) in &mut menus {
+        if menu.is_changed() || settings.is_changed() {
+            text.0 = menu.text(&settings);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        engine::Mode,
+        menu::{Menu, MenuItem, SETTINGS_ITEMS, cycle},
+    };
+
+    #[test]
+    fn test_selection_wraps_and_back_restores_it() {
+        let mut menu = Menu::new("PAUSED", vec![MenuItem::Resume, MenuItem::Settings]);
+
+        menu.move_by(-1);
+        assert_eq!(menu.selected(), MenuItem::Settings);
+
+        menu.open("SETTINGS", SETTINGS_ITEMS.to_vec());
+        assert_eq!(menu.selected(), MenuItem::Ghost);
+        assert!(!menu.is_top_level());
+
+        assert!(menu.back());
+        assert_eq!(menu.selected(), MenuItem::Settings);
+        assert!(!menu.back());
+    }
+
+    #[test]
+    fn test_cycle_wraps_both_ways() {
+        assert_eq!(cycle(&Mode::ALL, Mode::Marathon, -1), Mode::Endless);
+        assert_eq!(cycle(&Mode::ALL, Mode::Endless, 1), Mode::Marathon);
+        assert_eq!(cycle(&Mode::ALL, Mode::Sprint, 1), Mode::Ultra);
+    }
+}
@@ -1,45 +1,132 @@
-mod ishape;
-mod jshape;
-mod lshape;
-mod oshape;
-mod sshape;
-mod tshape;
-mod zshape;
+mod definitions;
+mod kicks;
 
-pub mod mesh_cache;
+use definitions::DEFINITIONS;
 
-use bevy::{asset::Handle, mesh::Mesh, transform::components::Transform};
+pub use kicks::Kick;
 
-pub use ishape::IShape;
-pub use jshape::JShape;
-pub use lshape::LShape;
-pub use oshape::OShape;
-pub use sshape::SShape;
-pub use tshape::TShape;
-pub use zshape::ZShape;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PieceKind {
+    I,
+    J,
+    L,
+    O,
+    S,
+    T,
+    Z,
+}
+
+impl PieceKind {
+    /// Every kind, in the order of `DEFINITIONS`.
+    pub const ALL: [PieceKind; DEFINITIONS.len()] = {
+        let mut all = [PieceKind::I; DEFINITIONS.len()];
+        let mut index = 0;
+
+        while index < all.len() {
+            all[index] = DEFINITIONS[index].kind;
+            index += 1;
+        }
+
+        all
+    };
+}
+
+/// Which wall kick data a piece rotates with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KickTable {
+    Jlstz,
+    I,
+    /// Rotates in place, for pieces that look the same in every state.
+    None,
+}
 
-use crate::consts::TILE_SIZE;
+/// Everything that makes up a kind of piece.
+pub struct PieceDef {
+    pub kind: PieceKind,
+    /// Every rotation state, in SRS order starting from spawn.
+    pub tables: &'static [Table],
+    pub kicks: KickTable,
+}
 
-pub type BoxedPiece = Box<dyn Piece + Send + Sync>;
+impl PieceKind {
+    /// `DEFINITIONS` lists the kinds in declaration order, so a kind is its own index.
+    pub fn definition(self) -> &'static PieceDef {
+        &DEFINITIONS[self as usize]
+    }
+}
 
-pub trait Piece {
-    fn rotate_cw(&mut self);
-    fn rotate_ccw(&mut self);
-    fn get_table(&self) -> &Table;
-    fn get_mesh(&self) -> &Handle<Mesh>;
+/// A piece of any kind, turning through the rotation states of its definition.
+#[derive(Clone)]
+pub struct Piece {
+    definition: &'static PieceDef,
+    rotation: usize,
 }
 
-pub fn get_piece_indicies(transform: &Transform) -> PieceIndicies {
-    PieceIndicies {
-        i: (-transform.translation.y / TILE_SIZE) as i32,
-        j: (transform.translation.x / TILE_SIZE) as i32,
+impl Piece {
+    pub fn new(kind: PieceKind) -> Self {
+        Piece {
+            definition: kind.definition(),
+            rotation: 0,
+        }
+    }
+
+    pub fn rotate_cw(&mut self) {
+        self.rotation = (self.rotation + 1) % self.definition.tables.len();
+    }
+
+    pub fn rotate_ccw(&mut self) {
+        let states = self.definition.tables.len();
+        self.rotation = (self.rotation + states - 1) % states;
+    }
+
+    pub fn get_kind(&self) -> PieceKind {
+        self.definition.kind
+    }
+
+    /// SRS rotation state: 0 (spawn), 1 (R), 2 or 3 (L).
+    pub fn get_rotation(&self) -> usize {
+        self.rotation
+    }
+
+    pub fn get_table(&self) -> &'static Table {
+        &self.definition.tables[self.rotation]
+    }
+
+    /// Offsets to try, in order, when rotating from state `from` to `to`.
+    pub fn get_kicks(&self, from: usize, to: usize) -> &'static [Kick] {
+        match self.definition.kicks {
+            KickTable::Jlstz => kicks::jlstz_kicks(from, to),
+            KickTable::I => kicks::i_kicks(from, to),
+            KickTable::None => &kicks::NO_KICKS,
+        }
     }
 }
 
 pub type Table = [[u8; 4]; 4];
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PieceIndicies {
     pub i: i32,
     pub j: i32,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::pieces::{PieceKind, definitions::DEFINITIONS};
+
+    #[test]
+    fn test_definitions_are_tetrominoes() {
+        for kind in PieceKind::ALL {
+            assert_eq!(kind.definition().kind, kind);
+        }
+
+        for definition in &DEFINITIONS {
+            assert!(matches!(definition.tables.len(), 1 | 4));
+
+            for table in definition.tables {
+                let cells = table.iter().flatten().filter(|&&cell| cell != 0).count();
+                assert_eq!(cells, 4, "{:?}", definition.kind);
+            }
+        }
+    }
+}
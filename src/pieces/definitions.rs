@@ -0,0 +1,216 @@
+//! Shapes of every piece, one `Table` per rotation state in SRS order.
+
+use crate::pieces::{KickTable, PieceDef, PieceKind, Table};
+
+const I0: Table = [
+    [0, 0, 0, 0], //
+    [1, 1, 1, 1], //
+    [0, 0, 0, 0], //
+    [0, 0, 0, 0], //
+];
+
+const I1: Table = [
+    [0, 0, 1, 0], //
+    [0, 0, 1, 0], //
+    [0, 0, 1, 0], //
+    [0, 0, 1, 0], //
+];
+
+const I2: Table = [
+    [0, 0, 0, 0], //
+    [0, 0, 0, 0], //
+    [1, 1, 1, 1], //
+    [0, 0, 0, 0], //
+];
+
+const I3: Table = [
+    [0, 1, 0, 0], //
+    [0, 1, 0, 0], //
+    [0, 1, 0, 0], //
+    [0, 1, 0, 0], //
+];
+
+const J0: Table = [
+    [1, 0, 0, 0], //
+    [1, 1, 1, 0], //
+    [0, 0, 0, 0], //
+    [0, 0, 0, 0], //
+];
+
+const J1: Table = [
+    [0, 1, 1, 0], //
+    [0, 1, 0, 0], //
+    [0, 1, 0, 0], //
+    [0, 0, 0, 0], //
+];
+
+const J2: Table = [
+    [0, 0, 0, 0], //
+    [1, 1, 1, 0], //
+    [0, 0, 1, 0], //
+    [0, 0, 0, 0], //
+];
+
+const J3: Table = [
+    [0, 1, 0, 0], //
+    [0, 1, 0, 0], //
+    [1, 1, 0, 0], //
+    [0, 0, 0, 0], //
+];
+
+const L0: Table = [
+    [0, 0, 1, 0], //
+    [1, 1, 1, 0], //
+    [0, 0, 0, 0], //
+    [0, 0, 0, 0], //
+];
+
+const L1: Table = [
+    [0, 1, 0, 0], //
+    [0, 1, 0, 0], //
+    [0, 1, 1, 0], //
+    [0, 0, 0, 0], //
+];
+
+const L2: Table = [
+    [0, 0, 0, 0], //
+    [1, 1, 1, 0], //
+    [1, 0, 0, 0], //
+    [0, 0, 0, 0], //
+];
+
+const L3: Table = [
+    [1, 1, 0, 0], //
+    [0, 1, 0, 0], //
+    [0, 1, 0, 0], //
+    [0, 0, 0, 0], //
+];
+
+const O0: Table = [
+    [1, 1, 0, 0], //
+    [1, 1, 0, 0], //
+    [0, 0, 0, 0], //
+    [0, 0, 0, 0], //
+];
+
+const S0: Table = [
+    [0, 1, 1, 0], //
+    [1, 1, 0, 0], //
+    [0, 0, 0, 0], //
+    [0, 0, 0, 0], //
+];
+
+const S1: Table = [
+    [0, 1, 0, 0], //
+    [0, 1, 1, 0], //
+    [0, 0, 1, 0], //
+    [0, 0, 0, 0], //
+];
+
+const S2: Table = [
+    [0, 0, 0, 0], //
+    [0, 1, 1, 0], //
+    [1, 1, 0, 0], //
+    [0, 0, 0, 0], //
+];
+
+const S3: Table = [
+    [1, 0, 0, 0], //
+    [1, 1, 0, 0], //
+    [0, 1, 0, 0], //
+    [0, 0, 0, 0], //
+];
+
+const T0: Table = [
+    [0, 1, 0, 0], //
+    [1, 1, 1, 0], //
+    [0, 0, 0, 0], //
+    [0, 0, 0, 0], //
+];
+
+const T1: Table = [
+    [0, 1, 0, 0], //
+    [0, 1, 1, 0], //
+    [0, 1, 0, 0], //
+    [0, 0, 0, 0], //
+];
+
+const T2: Table = [
+    [0, 0, 0, 0], //
+    [1, 1, 1, 0], //
+    [0, 1, 0, 0], //
+    [0, 0, 0, 0], //
+];
+
+const T3: Table = [
+    [0, 1, 0, 0], //
+    [1, 1, 0, 0], //
+    [0, 1, 0, 0], //
+    [0, 0, 0, 0], //
+];
+
+const Z0: Table = [
+    [1, 1, 0, 0], //
+    [0, 1, 1, 0], //
+    [0, 0, 0, 0], //
+    [0, 0, 0, 0], //
+];
+
+const Z1: Table = [
+    [0, 0, 1, 0], //
+    [0, 1, 1, 0], //
+    [0, 1, 0, 0], //
+    [0, 0, 0, 0], //
+];
+
+const Z2: Table = [
+    [0, 0, 0, 0], //
+    [1, 1, 0, 0], //
+    [0, 1, 1, 0], //
+    [0, 0, 0, 0], //
+];
+
+const Z3: Table = [
+    [0, 1, 0, 0], //
+    [1, 1, 0, 0], //
+    [1, 0, 0, 0], //
+    [0, 0, 0, 0], //
+];
+
+pub static DEFINITIONS: [PieceDef; 7] = [
+    PieceDef {
+        kind: PieceKind::I,
+        tables: &[I0, I1, I2, I3],
+        kicks: KickTable::I,
+    },
+    PieceDef {
+        kind: PieceKind::J,
+        tables: &[J0, J1, J2, J3],
+        kicks: KickTable::Jlstz,
+    },
+    PieceDef {
+        kind: PieceKind::L,
+        tables: &[L0, L1, L2, L3],
+        kicks: KickTable::Jlstz,
+    },
+    PieceDef {
+        kind: PieceKind::O,
+        tables: &[O0],
+        kicks: KickTable::None,
+    },
+    PieceDef {
+        kind: PieceKind::S,
+        tables: &[S0, S1, S2, S3],
+        kicks: KickTable::Jlstz,
+    },
+    PieceDef {
+        kind: PieceKind::T,
+        tables: &[T0, T1, T2, T3],
+        kicks: KickTable::Jlstz,
+    },
+    PieceDef {
+        kind: PieceKind::Z,
+        tables: &[Z0, Z1, Z2, Z3],
+        kicks: KickTable::Jlstz,
+    },
+];
@@ -0,0 +1,79 @@
+//! SRS wall kick data.
+//!
+//! Offsets are in guideline notation: `x` grows to the right and `y` grows
+//! upwards, so a `y` of `+1` means one row *up* in the `Matrix`.
+//! Rotation states are numbered 0 (spawn), 1 (R), 2 and 3 (L).
+
+/// A single `(x, y)` offset tried when rotating.
+pub type Kick = (i32, i32);
+
+pub const NO_KICKS: [Kick; 1] = [(0, 0)];
+
+const JLSTZ_KICKS: [[Kick; 5]; 8] = [
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // 0 -> R
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // R -> 0
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // R -> 2
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // 2 -> R
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // 2 -> L
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // L -> 2
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // L -> 0
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // 0 -> L
+];
+
+const I_KICKS: [[Kick; 5]; 8] = [
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)], // 0 -> R
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)], // R -> 0
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)], // R -> 2
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)], // 2 -> R
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)], // 2 -> L
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)], // L -> 2
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)], // L -> 0
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)], // 0 -> L
+];
+
+fn transition_index(from: usize, to: usize) -> Option<usize> {
+    match (from, to) {
+        (0, 1) => Some(0),
+        (1, 0) => Some(1),
+        (1, 2) => Some(2),
+        (2, 1) => Some(3),
+        (2, 3) => Some(4),
+        (3, 2) => Some(5),
+        (3, 0) => Some(6),
+        (0, 3) => Some(7),
+        _ => None,
+    }
+}
+
+pub fn jlstz_kicks(from: usize, to: usize) -> &'static [Kick] {
+    match transition_index(from, to) {
+        Some(index) => &JLSTZ_KICKS[index],
+        None => &NO_KICKS,
+    }
+}
+
+pub fn i_kicks(from: usize, to: usize) -> &'static [Kick] {
+    match transition_index(from, to) {
+        Some(index) => &I_KICKS[index],
+        None => &NO_KICKS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pieces::kicks::{i_kicks, jlstz_kicks};
+
+    #[test]
+    fn test_kicks_are_reversible() {
+        for (from, to) in [(0, 1), (1, 2), (2, 3), (3, 0)] {
+            for kicks in [jlstz_kicks, i_kicks] {
+                let forward = kicks(from, to);
+                let backward = kicks(to, from);
+
+                for (a, b) in forward.iter().zip(backward) {
+                    assert_eq!((a.0, a.1), (-b.0, -b.1));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+use crate::pieces::PieceKind;
+
+/// Decides which piece comes next.
+pub trait Randomizer {
+    fn next(&mut self) -> PieceKind;
+}
+
+pub type BoxedRandomizer = Box<dyn Randomizer + Send + Sync>;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RandomizerKind {
+    #[default]
+    Bag7,
+    Bag14,
+    Tgm,
+    Memoryless,
+}
+
+impl RandomizerKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "bag7" => Some(RandomizerKind::Bag7),
+            "bag14" => Some(RandomizerKind::Bag14),
+            "tgm" => Some(RandomizerKind::Tgm),
+            "random" => Some(RandomizerKind::Memoryless),
+            _ => None,
+        }
+    }
+
+    /// The name `parse` accepts.
+    pub fn name(self) -> &'static str {
+        match self {
+            RandomizerKind::Bag7 => "bag7",
+            RandomizerKind::Bag14 => "bag14",
+            RandomizerKind::Tgm => "tgm",
+            RandomizerKind::Memoryless => "random",
+        }
+    }
+
+    pub fn create(self, seed: u64) -> BoxedRandomizer {
+        match self {
+            RandomizerKind::Bag7 => Box::new(BagRandomizer::new(1, seed)),
+            RandomizerKind::Bag14 => Box::new(BagRandomizer::new(2, seed)),
+            RandomizerKind::Tgm => Box::new(HistoryRandomizer::new(4, seed)),
+            RandomizerKind::Memoryless => Box::new(MemorylessRandomizer::new(seed)),
+        }
+    }
+}
+
+/// Deals out shuffled bags holding `copies` of every piece.
+pub struct BagRandomizer {
+    rng: StdRng,
+    copies: usize,
+    bag: Vec<PieceKind>,
+}
+
+impl BagRandomizer {
+    pub fn new(copies: usize, seed: u64) -> Self {
+        BagRandomizer {
+            rng: StdRng::seed_from_u64(seed),
+            copies,
+            bag: Vec::new(),
+        }
+    }
+}
+
+impl Randomizer for BagRandomizer {
+    fn next(&mut self) -> PieceKind {
+        if self.bag.is_empty() {
+            for _ in 0..self.copies {
+                self.bag.extend(PieceKind::ALL);
+            }
+            self.bag.shuffle(&mut self.rng);
+        }
+
+        self.bag.pop().unwrap()
+    }
+}
+
+/// The TGM randomizer: tries up to `rolls` times for a pick that isn't one
+/// of the last four pieces dealt. The first piece is never S, Z or O.
+pub struct HistoryRandomizer {
+    rng: StdRng,
+    rolls: usize,
+    history: [PieceKind; 4],
+    first: bool,
+}
+
+impl HistoryRandomizer {
+    pub fn new(rolls: usize, seed: u64) -> Self {
+        HistoryRandomizer {
+            rng: StdRng::seed_from_u64(seed),
+            rolls,
+            history: [PieceKind::Z; 4],
+            first: true,
+        }
+    }
+}
+
+impl Randomizer for HistoryRandomizer {
+    fn next(&mut self) -> PieceKind {
+        let piece = if self.first {
+            self.first = false;
+            [PieceKind::I, PieceKind::J, PieceKind::L, PieceKind::T][self.rng.random_range(0..4)]
+        } else {
+            let mut piece = PieceKind::ALL[self.rng.random_range(0..PieceKind::ALL.len())];
+            for _ in 1..self.rolls {
+                if !self.history.contains(&piece) {
+                    break;
+                }
+                piece = PieceKind::ALL[self.rng.random_range(0..PieceKind::ALL.len())];
+            }
+            piece
+        };
+
+        self.history.rotate_right(1);
+        self.history[0] = piece;
+
+        piece
+    }
+}
+
+/// Every piece equally likely, every time.
+pub struct MemorylessRandomizer {
+    rng: StdRng,
+}
+
+impl MemorylessRandomizer {
+    pub fn new(seed: u64) -> Self {
+        MemorylessRandomizer {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Randomizer for MemorylessRandomizer {
+    fn next(&mut self) -> PieceKind {
+        PieceKind::ALL[self.rng.random_range(0..PieceKind::ALL.len())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        pieces::PieceKind,
+        randomizer::{BagRandomizer, Randomizer, RandomizerKind},
+    };
+
+    #[test]
+    fn test_bag_deals_every_piece_once() {
+        let mut randomizer = BagRandomizer::new(1, 42);
+
+        for _ in 0..10 {
+            let mut bag: Vec<PieceKind> = (0..7).map(|_| randomizer.next()).collect();
+            bag.sort();
+            assert_eq!(bag, PieceKind::ALL);
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        for kind in [
+            RandomizerKind::Bag7,
+            RandomizerKind::Bag14,
+            RandomizerKind::Tgm,
+            RandomizerKind::Memoryless,
+        ] {
+            let mut a = kind.create(7);
+            let mut b = kind.create(7);
+
+            for _ in 0..100 {
+                assert_eq!(a.next(), b.next());
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+mod mesh_cache;
+mod palette;
+
+pub use mesh_cache::MeshCache;
+pub use palette::Palette;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    math::Vec3,
+    mesh::{Indices, Mesh, PrimitiveTopology},
+};
+
+use crate::{
+    consts::{TILE_SIZE, VANISH_ROWS},
+    pieces::{PieceIndicies, Table},
+};
+
+/// World position of the top-left corner of a piece's `Table`.
+pub fn piece_translation(piece_indicies: &PieceIndicies) -> Vec3 {
+    Vec3::new(
+        piece_indicies.j as f32 * TILE_SIZE,
+        -(piece_indicies.i - VANISH_ROWS as i32) as f32 * TILE_SIZE,
+        0.0,
+    )
+}
+
+/// Builds a mesh with one quad per occupied cell of `table`,
+/// anchored at the table's top-left corner.
+pub fn table_mesh(table: &Table) -> Mesh {
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+
+    for (i, table_row) in table.iter().enumerate() {
+        for (j, &cell) in table_row.iter().enumerate() {
+            if cell == 0 {
+                continue;
+            }
+
+            let left = j as f32 * TILE_SIZE;
+            let top = -(i as f32) * TILE_SIZE;
+            let first = positions.len() as u32;
+
+            positions.extend([
+                [left, top, 0.0],
+                [left, top - TILE_SIZE, 0.0],
+                [left + TILE_SIZE, top, 0.0],
+                [left + TILE_SIZE, top - TILE_SIZE, 0.0],
+            ]);
+            indices.extend([first, first + 1, first + 2, first + 2, first + 1, first + 3]);
+        }
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// World position of the centre of a `Matrix` cell. The visible field starts
+/// at `y = 0`, so cells in the vanish zone end up above it.
+pub fn block_translation(row: usize, col: usize) -> Vec3 {
+    Vec3::new(
+        (col as f32 + 0.5) * TILE_SIZE,
+        -(row as f32 - VANISH_ROWS as f32 + 0.5) * TILE_SIZE,
+        0.0,
+    )
+}
@@ -0,0 +1,34 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{Assets, Handle},
+    ecs::resource::Resource,
+    mesh::Mesh,
+};
+
+use crate::{pieces::PieceKind, render::table_mesh};
+
+/// Mesh of every rotation state of every piece kind. Built once at startup
+/// so that spawning, rotating and previewing pieces never adds new assets.
+#[derive(Resource)]
+pub struct MeshCache {
+    handles: HashMap<(PieceKind, usize), Handle<Mesh>>,
+}
+
+impl MeshCache {
+    pub fn new(meshes: &mut Assets<Mesh>) -> Self {
+        let mut handles = HashMap::new();
+
+        for kind in PieceKind::ALL {
+            for (rotation, table) in kind.definition().tables.iter().enumerate() {
+                handles.insert((kind, rotation), meshes.add(table_mesh(table)));
+            }
+        }
+
+        MeshCache { handles }
+    }
+
+    pub fn get(&self, kind: PieceKind, rotation: usize) -> &Handle<Mesh> {
+        &self.handles[&(kind, rotation)]
+    }
+}
@@ -0,0 +1,77 @@
+use bevy::color::Color;
+
+use crate::{matrix::Cell, pieces::PieceKind};
+
+/// Colour scheme for pieces and the stack.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Palette {
+    /// Cyan I, yellow O, purple T, green S, red Z, blue J and orange L.
+    #[default]
+    Guideline,
+    /// Softer take on the guideline colours.
+    Pastel,
+    /// Grey shades only, told apart by brightness.
+    Monochrome,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [Palette::Guideline, Palette::Pastel, Palette::Monochrome];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "guideline" => Some(Palette::Guideline),
+            "pastel" => Some(Palette::Pastel),
+            "mono" => Some(Palette::Monochrome),
+            _ => None,
+        }
+    }
+
+    /// The name `parse` accepts.
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::Guideline => "guideline",
+            Palette::Pastel => "pastel",
+            Palette::Monochrome => "mono",
+        }
+    }
+
+    /// Every cell that can be drawn, for building materials up front.
+    pub fn cells() -> impl Iterator<Item = Cell> {
+        PieceKind::ALL
+            .into_iter()
+            .map(Cell::Piece)
+            .chain([Cell::Garbage])
+    }
+
+    pub fn color(self, cell: Cell) -> Color {
+        let (r, g, b) = match (self, cell) {
+            (_, Cell::Empty) => return Color::NONE,
+            (Palette::Monochrome, Cell::Garbage) => (0.3, 0.3, 0.3),
+            (_, Cell::Garbage) => (0.45, 0.45, 0.45),
+            (Palette::Guideline, Cell::Piece(kind)) => match kind {
+                PieceKind::I => (0.0, 0.9, 0.9),
+                PieceKind::J => (0.0, 0.3, 0.95),
+                PieceKind::L => (1.0, 0.55, 0.0),
+                PieceKind::O => (0.95, 0.9, 0.0),
+                PieceKind::S => (0.0, 0.85, 0.2),
+                PieceKind::T => (0.65, 0.1, 0.85),
+                PieceKind::Z => (0.95, 0.1, 0.15),
+            },
+            (Palette::Pastel, Cell::Piece(kind)) => match kind {
+                PieceKind::I => (0.6, 0.9, 0.95),
+                PieceKind::J => (0.6, 0.7, 0.95),
+                PieceKind::L => (0.98, 0.78, 0.6),
+                PieceKind::O => (0.98, 0.95, 0.65),
+                PieceKind::S => (0.65, 0.92, 0.7),
+                PieceKind::T => (0.8, 0.65, 0.92),
+                PieceKind::Z => (0.95, 0.65, 0.68),
+            },
+            (Palette::Monochrome, Cell::Piece(kind)) => {
+                let shade = 0.5 + 0.07 * kind as u8 as f32;
+                (shade, shade, shade)
+            }
+        };
+
+        Color::srgb(r, g, b)
+    }
+}
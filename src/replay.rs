@@ -0,0 +1,324 @@
+use std::{
+    cmp::Reverse,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bevy::ecs::resource::Resource;
+
+use crate::{
+    engine::{Action, Game, GameConfig, Inputs, Mode},
+    handling::Handling,
+    high_scores::Entry,
+    lock_delay::LockReset,
+    randomizer::RandomizerKind,
+};
+
+/// How many of the latest games are kept.
+pub const KEPT: usize = 5;
+
+/// A game as it was played. `Game` is deterministic, so the config, the
+/// seed and the inputs and time of every step are enough to play it again.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub config: GameConfig,
+    pub seed: u64,
+    steps: Vec<(Inputs, Duration)>,
+}
+
+impl Replay {
+    pub fn new(config: GameConfig, seed: u64) -> Self {
+        Replay {
+            config,
+            seed,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Adds a step, in the order they were passed to `Game::step`.
+    pub fn record(&mut self, inputs: Inputs, delta: Duration) {
+        self.steps.push((inputs, delta));
+    }
+
+    pub fn steps(&self) -> &[(Inputs, Duration)] {
+        &self.steps
+    }
+
+    /// The game as it was before the first step.
+    pub fn game(&self) -> Game {
+        Game::new(self.config.clone(), self.seed)
+    }
+
+    /// The game as it was after the last step.
+    pub fn play(&self) -> Game {
+        let mut game = self.game();
+
+        for (inputs, delta) in &self.steps {
+            game.step(inputs, *delta);
+        }
+
+        game
+    }
+
+    /// Parses the config, the `seed` and `steps` of `[held, pressed, delta_ns]`,
+    /// where `held` and `pressed` have a bit per `Action::ALL` entry.
+    /// Durations in the config are whole milliseconds, as the arguments give them.
+    pub fn parse(text: &str) -> Option<Self> {
+        let table: toml::Table = text.parse().ok()?;
+        let int = |name: &str| table.get(name)?.as_integer();
+        let name = |name: &str| table.get(name)?.as_str();
+        let ms = |name: &str| Some(Duration::from_millis(int(name)?.try_into().ok()?));
+
+        let config = GameConfig {
+            soft_drop_factor: table.get("soft_drop_factor")?.as_float()?,
+            handling: Handling {
+                das: ms("das_ms")?,
+                arr: ms("arr_ms")?,
+                das_cut: ms("das_cut_ms")?,
+            },
+            lock_delay: ms("lock_delay_ms")?,
+            lock_reset: LockReset::parse(name("lock_reset")?)?,
+            randomizer: RandomizerKind::parse(name("randomizer")?)?,
+            seed: None,
+            preview: int("preview")?.try_into().ok()?,
+            mode: Mode::parse(name("mode")?)?,
+            start_level: int("start_level")?.try_into().ok()?,
+        };
+
+        let steps = table
+            .get("steps")?
+            .as_array()?
+            .iter()
+            .map(parse_step)
+            .collect::<Option<_>>()?;
+
+        Some(Replay {
+            config,
+            // Stored with the bits of an `i64`, since TOML integers are signed.
+            seed: int("seed")? as u64,
+            steps,
+        })
+    }
+
+    fn to_toml(&self) -> String {
+        let config = &self.config;
+        let ms = |duration: Duration| toml::Value::from(duration.as_millis() as i64);
+        let mut table = toml::Table::new();
+
+        table.insert("seed".into(), (self.seed as i64).into());
+        table.insert("mode".into(), config.mode.name().into());
+        table.insert("start_level".into(), i64::from(config.start_level).into());
+        table.insert("randomizer".into(), config.randomizer.name().into());
+        table.insert("lock_reset".into(), config.lock_reset.name().into());
+        table.insert("lock_delay_ms".into(), ms(config.lock_delay));
+        table.insert("das_ms".into(), ms(config.handling.das));
+        table.insert("arr_ms".into(), ms(config.handling.arr));
+        table.insert("das_cut_ms".into(), ms(config.handling.das_cut));
+        table.insert("soft_drop_factor".into(), config.soft_drop_factor.into());
+        table.insert("preview".into(), (config.preview as i64).into());
+
+        let steps = self
+            .steps
+            .iter()
+            .map(|(inputs, delta)| step_to_toml(inputs, *delta))
+            .collect();
+        table.insert("steps".into(), toml::Value::Array(steps));
+
+        table.to_string()
+    }
+}
+
+fn action_bits(inputs: &Inputs, set: fn(&Inputs, Action) -> bool) -> i64 {
+    Action::ALL
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, action)| set(inputs, action))
+        .map(|(bit, _)| 1 << bit)
+        .sum()
+}
+
+fn step_to_toml(inputs: &Inputs, delta: Duration) -> toml::Value {
+    toml::Value::Array(vec![
+        action_bits(inputs, Inputs::held).into(),
+        action_bits(inputs, Inputs::just_pressed).into(),
+        (delta.as_nanos() as i64).into(),
+    ])
+}
+
+fn parse_step(value: &toml::Value) -> Option<(Inputs, Duration)> {
+    let [held, pressed, delta] = value.as_array()?.as_slice() else {
+        return None;
+    };
+    let (held, pressed) = (held.as_integer()?, pressed.as_integer()?);
+    let mut inputs = Inputs::default();
+
+    for (bit, action) in Action::ALL.into_iter().enumerate() {
+        if held & 1 << bit != 0 {
+            inputs.press(action, pressed & 1 << bit != 0);
+        }
+    }
+
+    Some((
+        inputs,
+        Duration::from_nanos(delta.as_integer()?.try_into().ok()?),
+    ))
+}
+
+/// The latest finished games, newest first, each with how it went.
+/// Kept one file per game next to the high scores.
+#[derive(Resource, Default)]
+pub struct Replays {
+    replays: Vec<(Replay, Entry)>,
+}
+
+impl Replays {
+    pub fn dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("tetris").join("replays"))
+    }
+
+    /// Reads the saved replays, playing each through for its result.
+    /// Files that can't be read or parsed are skipped.
+    pub fn load() -> Self {
+        let Some(dir) = Replays::dir() else {
+            return Replays::default();
+        };
+
+        let files = match saved_files(&dir) {
+            Ok(files) => files,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Replays::default(),
+            Err(err) => {
+                eprintln!("Can't read {}: {err}", dir.display());
+                return Replays::default();
+            }
+        };
+
+        let replays = files
+            .iter()
+            .take(KEPT)
+            .filter_map(|path| {
+                let text = fs::read_to_string(path)
+                    .map_err(|err| eprintln!("Can't read {}: {err}", path.display()))
+                    .ok()?;
+                let replay = Replay::parse(&text);
+                if replay.is_none() {
+                    eprintln!("Invalid replay in {}", path.display());
+                }
+                replay
+            })
+            .map(|replay| {
+                let entry = Entry::new(replay.play().stats());
+                (replay, entry)
+            })
+            .collect();
+
+        Replays { replays }
+    }
+
+    /// Adds a finished game as the newest replay and saves it, removing
+    /// the files of any older than the last `KEPT`.
+    pub fn record(&mut self, replay: Replay, entry: Entry) {
+        if let Some(dir) = Replays::dir()
+            && let Err(err) = save(&dir, &replay)
+        {
+            eprintln!("Can't save replay to {}: {err}", dir.display());
+        }
+
+        self.replays.insert(0, (replay, entry));
+        self.replays.truncate(KEPT);
+    }
+
+    pub fn get(&self) -> &[(Replay, Entry)] {
+        &self.replays
+    }
+}
+
+/// Replay files in `dir`, named after when they were saved, newest first.
+fn saved_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files: Vec<(u128, PathBuf)> = fs::read_dir(dir)?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let saved = path.file_stem()?.to_str()?.parse().ok()?;
+            (path.extension()? == "toml").then_some((saved, path))
+        })
+        .collect();
+
+    files.sort_by_key(|&(saved, _)| Reverse(saved));
+    Ok(files.into_iter().map(|(_, path)| path).collect())
+}
+
+fn save(dir: &Path, replay: &Replay) -> io::Result<()> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    fs::create_dir_all(dir)?;
+    fs::write(
+        dir.join(format!("{}.toml", now.as_millis())),
+        replay.to_toml(),
+    )?;
+
+    for old in saved_files(dir)?.iter().skip(KEPT) {
+        fs::remove_file(old)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        engine::{Action, Game, GameConfig, Inputs, Mode},
+        randomizer::RandomizerKind,
+        replay::Replay,
+    };
+
+    #[test]
+    fn test_saved_replay_plays_back_the_same_game() {
+        let config = GameConfig {
+            mode: Mode::Sprint,
+            randomizer: RandomizerKind::Tgm,
+            soft_drop_factor: 7.5,
+            start_level: 3,
+            ..GameConfig::default()
+        };
+        // Past `i64::MAX`, to check the seed survives being stored signed.
+        let seed = u64::MAX - 1;
+        let mut game = Game::new(config.clone(), seed);
+        let mut replay = Replay::new(config, seed);
+
+        let script = [
+            Action::MoveLeft,
+            Action::RotateCw,
+            Action::SoftDrop,
+            Action::HardDrop,
+            Action::Hold,
+            Action::MoveRight,
+            Action::Rotate180,
+            Action::HardDrop,
+        ];
+        for (frame, &action) in script.iter().cycle().take(400).enumerate() {
+            let mut inputs = Inputs::default();
+            inputs.press(action, frame % 3 != 1);
+            let delta = Duration::from_nanos(16_666_667 + frame as u64);
+
+            game.step(&inputs, delta);
+            replay.record(inputs, delta);
+        }
+
+        let parsed = Replay::parse(&replay.to_toml()).unwrap();
+        assert_eq!(parsed.seed, seed);
+        assert_eq!(parsed.config.randomizer, RandomizerKind::Tgm);
+        assert_eq!(parsed.config.soft_drop_factor, 7.5);
+        assert_eq!(parsed.steps(), replay.steps());
+
+        let played = parsed.play();
+        assert!(game.stats().pieces > 0);
+        assert_eq!(played.matrix(), game.matrix());
+        assert_eq!(played.stats().score, game.stats().score);
+        assert_eq!(played.stats().pieces, game.stats().pieces);
+        assert_eq!(played.stats().time, game.stats().time);
+    }
+}
@@ -0,0 +1,217 @@
+use std::time::Duration;
+
+use crate::tspin::TSpin;
+
+/// Lines needed to advance one level.
+pub const LINES_PER_LEVEL: u32 = 10;
+
+/// Gravity stops speeding up past this level, as in the guideline.
+const MAX_GRAVITY_LEVEL: u32 = 20;
+
+/// Time it takes the piece to fall one row at `level`, using the guideline
+/// formula `(0.8 - (level - 1) * 0.007) ^ (level - 1)` seconds.
+pub fn gravity_interval(level: u32) -> Duration {
+    let level = level.clamp(1, MAX_GRAVITY_LEVEL) as f64 - 1.0;
+
+    Duration::from_secs_f64((0.8 - level * 0.007).powf(level))
+}
+
+pub fn level_for(start_level: u32, lines: u32) -> u32 {
+    start_level + lines / LINES_PER_LEVEL
+}
+
+/// Points for locking a piece that cleared `lines` rows, before any bonuses.
+pub fn line_clear_score(lines: usize, tspin: TSpin, level: u32) -> u32 {
+    let base = match (tspin, lines) {
+        (TSpin::None, 0) => 0,
+        (TSpin::None, 1) => 100,
+        (TSpin::None, 2) => 300,
+        (TSpin::None, 3) => 500,
+        (TSpin::None, _) => 800,
+        (TSpin::Mini, 0) => 100,
+        (TSpin::Mini, 1) => 200,
+        (TSpin::Mini, _) => 400,
+        (TSpin::Full, 0) => 400,
+        (TSpin::Full, 1) => 800,
+        (TSpin::Full, 2) => 1200,
+        (TSpin::Full, _) => 1600,
+    };
+
+    base * level
+}
+
+/// Points for clearing the whole `Matrix`, before the level multiplier.
+fn perfect_clear_score(lines: usize, back_to_back: bool) -> u32 {
+    match lines {
+        1 => 800,
+        2 => 1200,
+        3 => 1800,
+        _ if back_to_back => 3200,
+        _ => 2000,
+    }
+}
+
+/// Extra garbage sent per combo step, capped at the last entry.
+const COMBO_ATTACK: [u32; 12] = [0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5];
+
+/// Garbage lines a clear would send to an opponent, guideline style.
+fn attack(lines: usize, tspin: TSpin, back_to_back: bool, combo: u32, perfect_clear: bool) -> u32 {
+    let base = match (tspin, lines) {
+        (_, 0) => return 0,
+        (TSpin::Full, lines) => 2 * lines as u32,
+        (TSpin::None, 4..) => 4,
+        (TSpin::None | TSpin::Mini, lines) => lines as u32 - 1,
+    };
+    let combo = COMBO_ATTACK[(combo as usize).min(COMBO_ATTACK.len() - 1)];
+    let perfect_clear = if perfect_clear { 10 } else { 0 };
+
+    base + back_to_back as u32 + combo + perfect_clear
+}
+
+/// Tetrises and T-spins that clear lines keep a back-to-back chain going.
+fn is_difficult(lines: usize, tspin: TSpin) -> bool {
+    lines >= 4 || (lines > 0 && tspin != TSpin::None)
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LockScore {
+    pub points: u32,
+    /// Consecutive line-clearing locks before this one, 0 if none.
+    pub combo: u32,
+    pub back_to_back: bool,
+    pub perfect_clear: bool,
+    /// Garbage lines this lock would send.
+    pub attack: u32,
+}
+
+/// Combo and back-to-back state carried between piece locks.
+pub struct ClearTracker {
+    /// -1 until a lock clears lines, then counts every further clearing lock.
+    combo: i32,
+    back_to_back: bool,
+}
+
+impl Default for ClearTracker {
+    fn default() -> Self {
+        ClearTracker {
+            combo: -1,
+            back_to_back: false,
+        }
+    }
+}
+
+impl ClearTracker {
+    pub fn on_lock(
+        &mut self,
+        lines: usize,
+        tspin: TSpin,
+        perfect_clear: bool,
+        level: u32,
+    ) -> LockScore {
+        let mut points = line_clear_score(lines, tspin, level);
+
+        if lines == 0 {
+            self.combo = -1;
+            return LockScore {
+                points,
+                ..Default::default()
+            };
+        }
+
+        self.combo += 1;
+
+        let difficult = is_difficult(lines, tspin);
+        let back_to_back = difficult && self.back_to_back;
+        self.back_to_back = difficult;
+
+        if back_to_back {
+            points += points / 2;
+        }
+
+        points += 50 * self.combo as u32 * level;
+
+        if perfect_clear {
+            points += perfect_clear_score(lines, back_to_back) * level;
+        }
+
+        let combo = self.combo as u32;
+
+        LockScore {
+            points,
+            combo,
+            back_to_back,
+            perfect_clear,
+            attack: attack(lines, tspin, back_to_back, combo, perfect_clear),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        scoring::{ClearTracker, gravity_interval, level_for, line_clear_score},
+        tspin::TSpin,
+    };
+
+    #[test]
+    fn test_gravity_interval() {
+        assert_eq!(gravity_interval(1), Duration::from_secs(1));
+        assert!((gravity_interval(2).as_secs_f64() - 0.793).abs() < 1e-9);
+        assert!(gravity_interval(15) < gravity_interval(14));
+
+        // The formula goes negative past level 115, so the speed is capped.
+        assert_eq!(gravity_interval(120), gravity_interval(20));
+        assert!(gravity_interval(u32::MAX) > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_levels_and_score() {
+        assert_eq!(level_for(1, 9), 1);
+        assert_eq!(level_for(1, 10), 2);
+        assert_eq!(level_for(5, 25), 7);
+
+        assert_eq!(line_clear_score(1, TSpin::None, 1), 100);
+        assert_eq!(line_clear_score(4, TSpin::None, 3), 2400);
+        assert_eq!(line_clear_score(0, TSpin::Mini, 1), 100);
+        assert_eq!(line_clear_score(2, TSpin::Full, 2), 2400);
+    }
+
+    #[test]
+    fn test_combo_and_back_to_back() {
+        let mut tracker = ClearTracker::default();
+
+        let tetris = tracker.on_lock(4, TSpin::None, false, 1);
+        assert_eq!(tetris.points, 800);
+        assert!(!tetris.back_to_back);
+        assert_eq!(tetris.attack, 4);
+
+        // Second difficult clear in a row: 1.5x plus one combo step.
+        let tsd = tracker.on_lock(2, TSpin::Full, false, 1);
+        assert_eq!(tsd.points, 1800 + 50);
+        assert!(tsd.back_to_back);
+        assert_eq!(tsd.combo, 1);
+        assert_eq!(tsd.attack, 4 + 1);
+
+        // A lock without lines breaks the combo but not back-to-back.
+        assert_eq!(tracker.on_lock(0, TSpin::None, false, 1).points, 0);
+        let tetris = tracker.on_lock(4, TSpin::None, false, 1);
+        assert_eq!(tetris.combo, 0);
+        assert!(tetris.back_to_back);
+
+        // A plain single breaks back-to-back.
+        assert!(!tracker.on_lock(1, TSpin::None, false, 1).back_to_back);
+        assert!(!tracker.on_lock(4, TSpin::None, false, 1).back_to_back);
+    }
+
+    #[test]
+    fn test_perfect_clear() {
+        let mut tracker = ClearTracker::default();
+
+        let score = tracker.on_lock(2, TSpin::None, true, 2);
+        assert!(score.perfect_clear);
+        assert_eq!(score.points, (300 + 1200) * 2);
+        assert_eq!(score.attack, 1 + 10);
+    }
+}
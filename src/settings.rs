@@ -0,0 +1,100 @@
+use std::{collections::HashSet, time::Duration};
+
+use bevy::prelude::*;
+
+use crate::{
+    consts::{MAX_PREVIEW, MAX_START_LEVEL},
+    engine::{GameConfig, Mode},
+    hud::HudStat,
+    lock_delay::LockReset,
+    randomizer::RandomizerKind,
+    render::Palette,
+};
+
+/// Options chosen before a game starts, plus display options that the
+/// settings menu can change at any time.
+#[derive(Resource, Deref)]
+pub struct Settings {
+    #[deref]
+    pub game: GameConfig,
+    pub palette: Palette,
+    /// Whether to show where the active piece would land.
+    pub ghost: bool,
+    /// HUD lines that are shown.
+    pub hud: HashSet<HudStat>,
+}
+
+impl Settings {
+    /// Reads `--palette <guideline|pastel|mono>`, `--lock-reset <extended|infinite|step>`,
+    /// `--lock-delay <ms>`, `--randomizer <bag7|bag14|tgm|random>`, `--seed <n>`,
+    /// `--preview <0-6>`, `--mode <marathon|sprint|ultra|endless>`, `--level <n>`,
+    /// `--das <ms>`, `--arr <ms>`, `--das-cut <ms>` and `--sdf <factor>`, keeping
+    /// the defaults for anything missing or malformed.
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Self {
+        let mut config = GameConfig::default();
+        let mut palette = Palette::default();
+
+        while let Some(arg) = args.next() {
+            let value = args.next().unwrap_or_default();
+
+            match arg.as_str() {
+                "--palette" => match Palette::parse(&value) {
+                    Some(parsed) => palette = parsed,
+                    None => eprintln!("Unknown palette {value:?}, using the default"),
+                },
+                "--lock-reset" => match LockReset::parse(&value) {
+                    Some(lock_reset) => config.lock_reset = lock_reset,
+                    None => eprintln!("Unknown lock reset mode {value:?}, using the default"),
+                },
+                "--lock-delay" => match value.parse() {
+                    Ok(ms) => config.lock_delay = Duration::from_millis(ms),
+                    Err(_) => eprintln!("Invalid lock delay {value:?}, using the default"),
+                },
+                "--randomizer" => match RandomizerKind::parse(&value) {
+                    Some(randomizer) => config.randomizer = randomizer,
+                    None => eprintln!("Unknown randomizer {value:?}, using the default"),
+                },
+                "--seed" => match value.parse() {
+                    Ok(seed) => config.seed = Some(seed),
+                    Err(_) => eprintln!("Invalid seed {value:?}, picking a random one"),
+                },
+                "--preview" => match value.parse::<usize>() {
+                    Ok(preview) => config.preview = preview.min(MAX_PREVIEW),
+                    Err(_) => eprintln!("Invalid preview count {value:?}, using the default"),
+                },
+                "--mode" => match Mode::parse(&value) {
+                    Some(mode) => config.mode = mode,
+                    None => eprintln!("Unknown mode {value:?}, using the default"),
+                },
+                "--level" => match value.parse::<u32>() {
+                    Ok(level) => config.start_level = level.clamp(1, MAX_START_LEVEL),
+                    Err(_) => eprintln!("Invalid level {value:?}, using the default"),
+                },
+                "--das" => match value.parse() {
+                    Ok(ms) => config.handling.das = Duration::from_millis(ms),
+                    Err(_) => eprintln!("Invalid DAS {value:?}, using the default"),
+                },
+                "--arr" => match value.parse() {
+                    Ok(ms) => config.handling.arr = Duration::from_millis(ms),
+                    Err(_) => eprintln!("Invalid ARR {value:?}, using the default"),
+                },
+                "--das-cut" => match value.parse() {
+                    Ok(ms) => config.handling.das_cut = Duration::from_millis(ms),
+                    Err(_) => eprintln!("Invalid DAS cut {value:?}, using the default"),
+                },
+                "--sdf" => match value.parse::<f64>() {
+                    Ok(factor) if factor >= 1.0 => config.soft_drop_factor = factor,
+                    _ => eprintln!("Invalid soft drop factor {value:?}, using the default"),
+                },
+                _ => eprintln!("Unknown argument {arg:?}"),
+            }
+        }
+
+        Settings {
+            game: config,
+            palette,
+            ghost: true,
+            hud: HashSet::from(HudStat::ALL),
+        }
+    }
+}
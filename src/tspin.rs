@@ -0,0 +1,96 @@
+use crate::{matrix::Matrix, pieces::PieceIndicies};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TSpin {
+    #[default]
+    None,
+    Mini,
+    Full,
+}
+
+/// Corners of the T's 3x3 box as `(row, col)`, clockwise from the top-left.
+const CORNERS: [(i32, i32); 4] = [(0, 0), (0, 2), (2, 2), (2, 0)];
+
+/// The two corners on either side of the T's point, for each rotation state.
+const FRONT_CORNERS: [[usize; 2]; 4] = [[0, 1], [1, 2], [2, 3], [3, 0]];
+
+/// The SRS kick that lets a mini be upgraded (the TST and fin kicks).
+const UPGRADE_KICK: usize = 4;
+
+/// Classifies a T piece that just locked after rotating into place with
+/// `kick`, using the 3-corner and front-corner rules.
+pub fn detect_tspin(
+    matrix: &Matrix,
+    piece_indicies: &PieceIndicies,
+    rotation: usize,
+    kick: usize,
+) -> TSpin {
+    let occupied =
+        CORNERS.map(|(i, j)| matrix.is_occupied(piece_indicies.i + i, piece_indicies.j + j));
+
+    if occupied.iter().filter(|&&corner| corner).count() < 3 {
+        return TSpin::None;
+    }
+
+    let front = FRONT_CORNERS[rotation]
+        .iter()
+        .all(|&corner| occupied[corner]);
+
+    if front || kick == UPGRADE_KICK {
+        TSpin::Full
+    } else {
+        TSpin::Mini
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        consts::{COLS, ROWS},
+        matrix::{Cell, Matrix},
+        pieces::PieceIndicies,
+        tspin::{TSpin, detect_tspin},
+    };
+
+    #[test]
+    fn test_tspin_double_slot() {
+        let mut matrix = Matrix::try_new(COLS as usize, ROWS as usize).unwrap();
+        let bottom = ROWS as usize - 1;
+
+        // Pointing down into a slot with an overhang on the left.
+        matrix.set(bottom - 2, 0, Cell::Garbage);
+        matrix.set(bottom - 2, 2, Cell::Garbage);
+        matrix.set(bottom, 0, Cell::Garbage);
+        matrix.set(bottom, 2, Cell::Garbage);
+        let indicies = PieceIndicies {
+            i: bottom as i32 - 2,
+            j: 0,
+        };
+
+        assert_eq!(detect_tspin(&matrix, &indicies, 2, 0), TSpin::Full);
+    }
+
+    #[test]
+    fn test_tspin_mini_against_wall() {
+        let mut matrix = Matrix::try_new(COLS as usize, ROWS as usize).unwrap();
+        let bottom = ROWS as usize - 1;
+
+        // Pointing right with its flat back against the left wall, which fills the back corners.
+        matrix.set(bottom, 1, Cell::Garbage);
+        let indicies = PieceIndicies {
+            i: bottom as i32 - 2,
+            j: -1,
+        };
+
+        assert_eq!(detect_tspin(&matrix, &indicies, 1, 1), TSpin::Mini);
+        assert_eq!(detect_tspin(&matrix, &indicies, 1, 4), TSpin::Full);
+    }
+
+    #[test]
+    fn test_no_tspin_in_open_space() {
+        let matrix = Matrix::try_new(COLS as usize, ROWS as usize).unwrap();
+        let indicies = PieceIndicies { i: 5, j: 3 };
+
+        assert_eq!(detect_tspin(&matrix, &indicies, 0, 0), TSpin::None);
+    }
+}